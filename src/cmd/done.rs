@@ -1,11 +1,26 @@
 use crate::{command, config::Config, git, prompt::Prompter, session};
 use anyhow::Result;
-use session::State;
+use clap::Parser;
+use session::{DoneStrategy, State};
+use std::fs;
+
+#[derive(Parser, Debug, Default)]
+pub struct DoneOpts {
+    /// Commit the squashed changes with this message instead of leaving
+    /// them staged
+    #[clap(short, long)]
+    pub message: Option<String>,
+
+    /// How to finish, instead of the session setting
+    #[clap(long, value_enum)]
+    pub strategy: Option<DoneStrategy>,
+}
 
 pub struct Done<'a> {
     git: &'a dyn git::Git,
     store: &'a dyn session::Store,
     prompter: &'a dyn Prompter,
+    opts: DoneOpts,
     config: Config,
 }
 
@@ -14,28 +29,30 @@ impl<'a> Done<'a> {
         git: &'a impl git::Git,
         store: &'a impl session::Store,
         prompter: &'a impl Prompter,
+        opts: DoneOpts,
         config: Config,
     ) -> Done<'a> {
         Self {
             git,
             store,
             prompter,
+            opts,
             config,
         }
     }
 
     pub fn run(&self) -> Result<()> {
-        let me = self.config.name.clone();
-        command::run_hook(&self.config.hooks.before_done, &me, "")?;
+        command::run_hook(&self.config.hooks.before_done, &self.config.name, "", "")?;
 
-        let session = self.store.load()?;
+        let me = self.config.key();
+        let session = self.store.load()?.identify(&self.config.driver());
         match &session.state {
             State::Stopped => {
                 log::warn!("No current mob session, run mob start");
             }
-            State::Working { driver } if driver == me.as_str() => self.done(session)?,
-            State::Working { driver } => {
-                log::warn!("{} is currently working", driver);
+            State::Working { driver, .. } if driver == me.as_str() => self.done(session)?,
+            State::Working { driver, .. } => {
+                log::warn!("{} is currently working", session.drivers.name(driver));
                 let take_over = self
                     .prompter
                     .confirm("Merge anyway with risk of loosing work?", false)?;
@@ -44,7 +61,13 @@ impl<'a> Done<'a> {
                     self.done(session)?;
                 }
             }
-            State::WaitingForNext { .. } => self.done(session)?,
+            State::WaitingForNext { .. } | State::Break { .. } => self.done(session)?,
+            paused @ State::Paused { .. } => {
+                log::warn!(
+                    "The mob is {}, run 'mob resume' first",
+                    paused.pause_description().unwrap_or_default()
+                );
+            }
         }
         Ok(())
     }
@@ -70,49 +93,169 @@ impl<'a> Done<'a> {
             ])?;
         }
 
+        let branch = session.branches.branch.as_str();
+        let base_branch = session.branches.base_branch.as_str();
         let remote_branches = session.branches.with_remote(&self.config.remote);
+        let commit = self.git.commit_id(branch)?;
+        let strategy = self.opts.strategy.unwrap_or_else(|| {
+            session
+                .settings
+                .as_ref()
+                .map(|settings| settings.done_strategy)
+                .unwrap_or_default()
+        });
 
-        self.git.run(&[
-            "push",
-            "--no-verify",
-            self.config.remote.as_str(),
-            session.branches.branch.as_str(),
-        ])?;
+        self.git
+            .run(&["push", "--no-verify", self.config.remote.as_str(), branch])?;
 
         self.git.run(&["fetch", "--all", "--prune"])?;
-        self.git
-            .run(&["checkout", session.branches.base_branch.as_str()])?;
+        self.git.run(&["checkout", base_branch])?;
 
         self.git
             .run(&["merge", remote_branches.base_branch.as_str(), "--ff-only"])?;
 
-        self.git.run(&[
-            "merge",
-            "--squash",
-            "--ff",
-            session.branches.branch.as_str(),
-        ])?;
+        // Credit everyone who committed on the mob branch
+        let authors = self.git.authors("HEAD", branch)?;
+        let co_authors = session
+            .drivers
+            .co_authors(&authors, &self.config.driver())
+            .join("\n");
+
+        match strategy {
+            DoneStrategy::Squash => self.squash(branch, &co_authors, false)?,
+            DoneStrategy::SquashCommit => self.squash(branch, &co_authors, true)?,
+            DoneStrategy::Merge => self.merge(branch, &co_authors)?,
+            DoneStrategy::Rebase => self.rebase(branch, base_branch, false)?,
+            DoneStrategy::RebaseAutosquash => self.rebase(branch, base_branch, true)?,
+            DoneStrategy::Review => self.review(branch, base_branch, &co_authors)?,
+        }
 
         // Delete mob branch
-        self.git
-            .run(&["branch", "-D", session.branches.branch.as_str()])?;
+        self.git.backup(branch)?;
+        self.git.run(&["branch", "-D", branch])?;
         self.git.run(&[
             "push",
             "--no-verify",
             &self.config.remote,
             "--delete",
-            session.branches.branch.as_str(),
+            branch,
         ])?;
 
-        log::info!("Run git diff --staged and then");
-        log::info!("git commit -m 'describe what changed'");
+        let state = session.state.clone();
+        self.store.transition(session, &|session| {
+            session::ensure_unchanged(&state, &session)?;
+            let session = session::Session {
+                state: State::Stopped,
+                ..session
+            };
+            let event =
+                session::Event::new(&self.config.key(), session::EventKind::Done, commit.clone());
+            Ok((session, vec![event]))
+        })?;
+        command::run_hook(&self.config.hooks.after_done, &self.config.name, "", "")?;
+        Ok(())
+    }
+
+    /// Squashes the mob branch into the current branch, committing it if
+    /// asked to or if there is a message
+    fn squash(&self, branch: &str, co_authors: &str, commit: bool) -> Result<()> {
+        self.git.run(&["merge", "--squash", "--ff", branch])?;
 
-        let session = session::Session {
-            state: State::Stopped,
-            ..session
+        match &self.opts.message {
+            Some(message) if self.git.tree_is_clean()? => {
+                log::info!("Nothing changed, not committing '{}'", message);
+            }
+            Some(message) => {
+                self.git
+                    .run(&["commit", "--message", &with_trailers(message, co_authors)])?;
+            }
+            None => {
+                // git commit uses SQUASH_MSG as the template
+                if !co_authors.is_empty() {
+                    if self.config.dry_run {
+                        log::info!("Would add to the commit message:\n{}", co_authors);
+                    } else {
+                        let path = self.git.git_path("SQUASH_MSG")?;
+                        let squash_msg = fs::read_to_string(&path).unwrap_or_default();
+                        fs::write(&path, format!("{}\n{}\n", squash_msg, co_authors))?;
+                    }
+                }
+                if !commit {
+                    log::info!("Run git diff --staged and then");
+                    log::info!("git commit");
+                } else if self.git.tree_is_clean()? {
+                    log::info!("Nothing changed, not committing");
+                } else {
+                    self.git.run(&["commit", "--no-edit"])?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn merge(&self, branch: &str, co_authors: &str) -> Result<()> {
+        let message = self
+            .opts
+            .message
+            .clone()
+            .unwrap_or_else(|| format!("Merge mob branch '{}'", branch));
+        self.git.run(&[
+            "merge",
+            "--no-ff",
+            "--message",
+            &with_trailers(&message, co_authors),
+            branch,
+        ])
+    }
+
+    /// Rebases the mob branch onto the base branch and fast-forwards the base
+    /// branch, the turns keep their own authors
+    fn rebase(&self, branch: &str, base_branch: &str, autosquash: bool) -> Result<()> {
+        if self.opts.message.is_some() {
+            log::warn!("Rebasing keeps the commits of every turn, ignoring the message");
+        }
+
+        self.git.run(&["checkout", branch])?;
+        let rebased = if autosquash {
+            // An interactive rebase with a no-op editor applies the todo list as is
+            self.git.run(&[
+                "-c",
+                "sequence.editor=:",
+                "rebase",
+                "--interactive",
+                "--autosquash",
+                base_branch,
+            ])
+        } else {
+            self.git.run(&["rebase", base_branch])
         };
-        self.store.save(session)?;
-        command::run_hook(&self.config.hooks.after_done, &self.config.name, "")?;
+        if let Err(err) = rebased {
+            self.git.run(&["rebase", "--abort"])?;
+            self.git.run(&["checkout", base_branch])?;
+            return Err(err.context(format!(
+                "Could not rebase {} onto {}, try another --strategy",
+                branch, base_branch
+            )));
+        }
+
+        self.git.run(&["checkout", base_branch])?;
+        self.git.run(&["merge", "--ff-only", branch])
+    }
+
+    /// Squashes the mob branch onto a new branch and pushes it
+    fn review(&self, branch: &str, base_branch: &str, co_authors: &str) -> Result<()> {
+        let review_branch = format!("{}-review", branch);
+        self.git
+            .run(&["checkout", "-b", &review_branch, base_branch])?;
+        self.squash(branch, co_authors, true)?;
+        self.git.run(&[
+            "push",
+            "--no-verify",
+            "--set-upstream",
+            &self.config.remote,
+            &review_branch,
+        ])?;
+        log::info!("Pushed {} for review", review_branch);
         Ok(())
     }
 
@@ -123,3 +266,11 @@ impl<'a> Done<'a> {
         })
     }
 }
+
+fn with_trailers(message: &str, co_authors: &str) -> String {
+    if co_authors.is_empty() {
+        message.to_string()
+    } else {
+        format!("{}\n\n{}", message, co_authors)
+    }
+}
@@ -0,0 +1,91 @@
+use crate::{config::Config, prompt::Prompter, session};
+use anyhow::{Result, anyhow};
+use clap::Parser;
+use session::{Event, EventKind};
+
+#[derive(Parser, Debug)]
+pub struct JoinOpts {
+    /// Join the rotation after this driver
+    #[clap(long)]
+    pub after: Option<String>,
+}
+
+pub struct Join<'a> {
+    store: &'a dyn session::Store,
+    prompter: &'a dyn Prompter,
+    opts: JoinOpts,
+    config: Config,
+}
+
+impl<'a> Join<'a> {
+    pub fn new(
+        opts: JoinOpts,
+        store: &'a impl session::Store,
+        prompter: &'a impl Prompter,
+        config: Config,
+    ) -> Join<'a> {
+        Self {
+            store,
+            prompter,
+            opts,
+            config,
+        }
+    }
+
+    pub fn run(&self) -> Result<()> {
+        let me = self.config.driver();
+        let session = self.store.load_or_default()?.identify(&me);
+
+        if session.drivers.find(&me).is_some() {
+            if session.drivers.is_away(me.key()) {
+                log::info!("You are already part of the mob, run 'mob back' if you were away");
+            } else {
+                log::info!("You are already part of the mob");
+            }
+            return Ok(());
+        }
+
+        let after = self.after(&session.drivers)?;
+        let history = self.store.history()?;
+
+        let session = self.store.transition(session, &|session| {
+            let session = session::Session {
+                drivers: session.drivers.clone().insert(after.clone(), me.clone()),
+                ..session
+            }
+            .with_navigator(&history);
+            Ok((session, vec![Event::new(me.key(), EventKind::Join, None)]))
+        })?;
+
+        let after = after.map(|after| session.drivers.name(&after));
+
+        match after {
+            Some(after) => log::info!("You joined the mob after {}", after),
+            None => log::info!("You joined the mob"),
+        }
+        Ok(())
+    }
+
+    fn after(&self, drivers: &session::Drivers) -> Result<Option<String>> {
+        if let Some(after) = &self.opts.after {
+            return match drivers.get(after) {
+                Some(driver) => Ok(Some(driver.key().to_string())),
+                None => Err(anyhow!("{} is not part of the mob", after)),
+            };
+        }
+
+        let keys = drivers.all();
+        if keys.is_empty() {
+            return Ok(None);
+        }
+
+        let names: Vec<String> = keys.iter().map(|key| drivers.name(key)).collect();
+        let names_strs: Vec<&str> = names.iter().map(|s| s.as_str()).collect();
+        let after = self.prompter.select_with_prompt(
+            "Join after which driver?",
+            &names_strs,
+            keys.len() - 1,
+        )?;
+        Ok(Some(keys[after].clone()))
+    }
+}
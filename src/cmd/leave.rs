@@ -0,0 +1,61 @@
+use crate::{config::Config, session};
+use anyhow::{Result, anyhow};
+use session::{Event, EventKind, State};
+
+pub struct Leave<'a> {
+    store: &'a dyn session::Store,
+    config: Config,
+}
+
+impl<'a> Leave<'a> {
+    pub fn new(store: &'a impl session::Store, config: Config) -> Leave<'a> {
+        Self { store, config }
+    }
+
+    pub fn run(&self) -> Result<()> {
+        let me = self.config.key();
+        let me = me.as_str();
+        let session = self.store.load()?.identify(&self.config.driver());
+
+        if !session.drivers.contains(me) {
+            return Err(anyhow!("You are not part of the mob"));
+        }
+
+        if let State::Working { driver, .. } = session.state.active()
+            && driver == me
+        {
+            return Err(anyhow!(
+                "You are driving, run 'mob next' or 'mob done' before leaving"
+            ));
+        }
+
+        let history = self.store.history()?;
+        let session = self.store.transition(session, &|session| {
+            if !session.drivers.contains(me) {
+                return Ok((session, vec![]));
+            }
+            // Whoever follows me is next instead
+            let next = session.drivers.next(me).filter(|next| next != me);
+            let state = session.state.replace_next(me, next);
+
+            let session = session::Session {
+                drivers: session.drivers.remove(me),
+                state,
+                ..session
+            }
+            .with_navigator(&history);
+            Ok((session, vec![Event::new(me, EventKind::Leave, None)]))
+        })?;
+
+        match &session.state {
+            State::WaitingForNext { next: Some(next) } => {
+                log::info!("Next driver: {}", session.drivers.name(next))
+            }
+            State::WaitingForNext { next: None } => log::info!("Next driver: anyone"),
+            _ => {}
+        }
+
+        log::info!("You left the mob, run 'mob join' to come back");
+        Ok(())
+    }
+}
@@ -0,0 +1,156 @@
+use crate::{duration, git, session};
+use anyhow::Result;
+use chrono::{DateTime, Local, Utc};
+use clap::Parser;
+use console::style;
+use serde::Serialize;
+
+#[derive(Parser, Debug)]
+pub struct LogOpts {
+    /// Only show turns by this driver
+    #[clap(short, long)]
+    pub driver: Option<String>,
+
+    /// Only show turns within this long ago, e.g. 45m, 2h or 1d
+    #[clap(short, long)]
+    pub since: Option<String>,
+
+    /// Only show turns started before this long ago, e.g. 45m, 2h or 1d
+    #[clap(short, long)]
+    pub until: Option<String>,
+
+    /// Print turns as json
+    #[clap(long)]
+    pub json: bool,
+}
+
+#[derive(Debug, Serialize)]
+struct Entry {
+    driver: String,
+    started: DateTime<Utc>,
+    ended: Option<DateTime<Utc>>,
+    minutes: i64,
+    /// None if the commits are unknown or missing locally
+    files: Option<Vec<String>>,
+    from_commit: Option<String>,
+    to_commit: Option<String>,
+}
+
+pub struct Log<'a> {
+    git: &'a dyn git::Git,
+    store: &'a dyn session::Store,
+    opts: LogOpts,
+}
+
+impl<'a> Log<'a> {
+    pub fn new(opts: LogOpts, git: &'a impl git::Git, store: &'a impl session::Store) -> Log<'a> {
+        Self { git, store, opts }
+    }
+
+    pub fn run(&self) -> Result<()> {
+        let now = Utc::now();
+        // Turns are recorded by key, the session knows the names
+        let drivers = self.store.load_read_only()?.drivers;
+        let key = |driver: &str| match drivers.get(driver) {
+            Some(found) => found.key().to_string(),
+            None => driver.to_string(),
+        };
+        let wanted = self.opts.driver.as_deref().map(key);
+        let since = self
+            .opts
+            .since
+            .as_deref()
+            .map(duration::parse)
+            .transpose()?;
+        let until = self
+            .opts
+            .until
+            .as_deref()
+            .map(duration::parse)
+            .transpose()?;
+
+        let entries: Vec<Entry> = self
+            .store
+            .history()?
+            .turns()
+            .into_iter()
+            .filter(|turn| match &wanted {
+                Some(driver) => &key(&turn.driver) == driver,
+                None => true,
+            })
+            .filter(|turn| match since {
+                Some(since) => turn.ended.unwrap_or(now) >= now - since,
+                None => true,
+            })
+            .filter(|turn| match until {
+                Some(until) => turn.started <= now - until,
+                None => true,
+            })
+            .map(|turn| Entry {
+                minutes: (turn.ended.unwrap_or(now) - turn.started).num_minutes(),
+                files: self.files(&turn.from_commit, &turn.to_commit),
+                driver: match drivers.get(&turn.driver) {
+                    Some(found) => drivers.name(found.key()),
+                    None => turn.driver,
+                },
+                started: turn.started,
+                ended: turn.ended,
+                from_commit: turn.from_commit,
+                to_commit: turn.to_commit,
+            })
+            .collect();
+
+        if self.opts.json {
+            println!("{}", serde_json::to_string_pretty(&entries)?);
+            return Ok(());
+        }
+
+        if entries.is_empty() {
+            log::info!("No turns found");
+        }
+        for entry in entries {
+            Log::print_entry(&entry);
+        }
+        Ok(())
+    }
+
+    fn files(&self, from: &Option<String>, to: &Option<String>) -> Option<Vec<String>> {
+        let (from, to) = (from.as_ref()?, to.as_ref()?);
+        self.git
+            .changed_files(from, to)
+            .map_err(|err| log::trace!("Could not diff {}..{}: {}", from, to, err))
+            .ok()
+    }
+
+    fn print_entry(entry: &Entry) {
+        let started = entry.started.with_timezone(&Local).format("%Y-%m-%d %H:%M");
+        let length = duration::format(chrono::Duration::minutes(entry.minutes)).human();
+        let length = match entry.ended {
+            Some(_) => length,
+            None => format!("driving for {}", length),
+        };
+        let files = match &entry.files {
+            Some(files) if files.len() == 1 => "1 file".to_string(),
+            Some(files) => format!("{} files", files.len()),
+            None => "? files".to_string(),
+        };
+        let short = |commit: &Option<String>| match commit {
+            Some(commit) => commit.chars().take(7).collect(),
+            None => "?".to_string(),
+        };
+
+        println!(
+            "{} {:<12} {:<24} {:<10} {}",
+            style(started).cyan(),
+            style(&entry.driver).bold(),
+            length,
+            files,
+            style(format!(
+                "{}..{}",
+                short(&entry.from_commit),
+                short(&entry.to_commit)
+            ))
+            .yellow(),
+        );
+    }
+}
@@ -1,10 +1,32 @@
 mod done;
+mod join;
+mod leave;
+mod log;
 mod next;
 mod order;
+mod pause;
+mod presence;
+mod rescue;
+mod resume;
+mod settings;
+mod skip;
 mod start;
 mod status;
-pub use done::Done;
+mod sync_base;
+mod undo;
+pub use done::{Done, DoneOpts};
+pub use join::{Join, JoinOpts};
+pub use leave::Leave;
+pub use log::{Log, LogOpts};
 pub use next::Next;
 pub use order::Order;
+pub use pause::{Pause, PauseOpts};
+pub use presence::{Presence, PresenceOpts};
+pub use rescue::Rescue;
+pub use resume::Resume;
+pub use settings::{Settings, SettingsOpts};
+pub use skip::Skip;
 pub use start::{Start, StartOpts};
 pub use status::{Status, StatusOpts};
+pub use sync_base::{SyncBase, SyncBaseOpts};
+pub use undo::Undo;
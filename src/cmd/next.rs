@@ -1,44 +1,71 @@
-use crate::{command, config::Config, git, session};
+use crate::{command, config::Config, git, prompt::Prompter, session};
 use anyhow::Result;
-use session::State;
+use chrono::{Local, Utc};
+use session::{Navigator, Rotation, State};
 
 pub struct Next<'a> {
     git: &'a dyn git::Git,
     store: &'a dyn session::Store,
+    prompter: &'a dyn Prompter,
     config: Config,
 }
 
 impl<'a> Next<'a> {
-    pub fn new(git: &'a impl git::Git, store: &'a impl session::Store, config: Config) -> Next<'a> {
-        Self { git, store, config }
+    pub fn new(
+        git: &'a impl git::Git,
+        store: &'a impl session::Store,
+        prompter: &'a impl Prompter,
+        config: Config,
+    ) -> Next<'a> {
+        Self {
+            git,
+            store,
+            prompter,
+            config,
+        }
     }
 
     pub fn run(&self) -> Result<()> {
-        let me = &self.config.name;
-        command::run_hook(&self.config.hooks.before_next, me, "")?;
+        command::run_hook(&self.config.hooks.before_next, &self.config.name, "", "")?;
 
-        let session = self.store.load()?;
+        let me = &self.config.key();
+        let session = self.store.load()?.identify(&self.config.driver());
 
-        match &session.state {
+        match &session.state.clone().end_break(Utc::now()) {
             State::Stopped => {
                 log::warn!("No current mob session, run mob start");
             }
-            State::Working { driver } if driver != me.as_str() => {
-                log::warn!("The current driver is {}", driver);
+            State::Working { driver, .. } if driver != me.as_str() => {
+                log::warn!("The current driver is {}", session.drivers.name(driver));
             }
             State::Working { .. } => self.next(session)?,
             State::WaitingForNext { next, .. } => {
                 match next {
                     Some(name) if name == me.as_str() => log::info!("It's your turn. Run start"),
-                    Some(name) => log::info!("Waiting for {} to start", name),
+                    Some(name) => {
+                        log::info!("Waiting for {} to start", session.drivers.name(name))
+                    }
                     None => log::info!("Waiting for someone to run start"),
                 };
             }
+            State::Break { until, .. } => {
+                log::info!(
+                    "The mob is on a break until {}",
+                    until.with_timezone(&Local).format("%H:%M")
+                );
+            }
+            paused @ State::Paused { .. } => {
+                log::warn!(
+                    "The mob is {}, run 'mob resume' first",
+                    paused.pause_description().unwrap_or_default()
+                );
+            }
         };
         Ok(())
     }
 
     fn next(&self, session: session::Session) -> Result<()> {
+        let before = self.git.commit_id("HEAD")?;
         if self.git.tree_is_clean()? {
             log::info!("Nothing was changed, so nothing to commit");
         } else {
@@ -58,26 +85,144 @@ impl<'a> Next<'a> {
             ])?;
         }
 
-        let next_driver = session.drivers.next(&self.config.name);
+        let history = self.store.history()?;
+        let next_driver = self.next_driver(&session, &history)?;
         let next_driver_name = match next_driver {
-            Some(ref driver) => driver,
-            None => "anyone",
+            Some(ref driver) => session.drivers.name(driver),
+            None => "anyone".to_string(),
         };
 
-        let session = session::Session {
-            state: State::WaitingForNext {
-                next: next_driver.clone(),
-            },
-            ..session
-        };
+        let state = session.state.clone();
+        let commit = self.git.commit_id("HEAD")?;
+        let session = self.store.transition(session, &|session| {
+            session::ensure_unchanged(&state, &session)?;
+            Ok(self.hand_over(session, &next_driver, (&before, &commit), &history))
+        })?;
 
-        self.store.save(session)?;
+        let navigator_name = session
+            .navigator
+            .as_deref()
+            .map(|navigator| session.drivers.name(navigator));
+        if let State::Break { until, .. } = &session.state {
+            log::info!(
+                "☕ Time for a break! Back at {}",
+                until.with_timezone(&Local).format("%H:%M")
+            );
+        }
         log::info!("Next driver: {}", next_driver_name);
+        if let Some(navigator) = &navigator_name {
+            log::info!("Navigator: {}", navigator);
+        }
         command::run_hook(
             &self.config.hooks.after_next,
             &self.config.name,
-            next_driver_name,
+            &next_driver_name,
+            navigator_name.as_deref().unwrap_or_default(),
         )?;
         Ok(())
     }
+
+    /// The session after handing over to `next_driver`, with a break if due.
+    /// `commits` are the mob branch before and after committing the work.
+    fn hand_over(
+        &self,
+        session: session::Session,
+        next_driver: &Option<String>,
+        (before, commit): (&Option<String>, &Option<String>),
+        history: &session::History,
+    ) -> (session::Session, Vec<session::Event>) {
+        let navigator = next_driver
+            .as_ref()
+            .and_then(|driver| session.navigator_for(driver, history));
+
+        let now = Utc::now();
+        let (turn_time, work_duration) = match &session.state {
+            State::Working {
+                started,
+                work_duration,
+                ..
+            } => ((now - *started).num_minutes(), Some(*work_duration)),
+            _ => (0, None),
+        };
+        let session = session::Session {
+            mob_time: session.mob_time + turn_time,
+            navigator,
+            ..session
+        };
+
+        let session = match session.break_due(now.with_timezone(&Local)) {
+            Some(until) => session::Session {
+                state: State::Break {
+                    next: next_driver.clone(),
+                    until,
+                },
+                last_break: Some(now),
+                mob_time: 0,
+                ..session
+            },
+            None => session::Session {
+                state: State::WaitingForNext {
+                    next: next_driver.clone(),
+                },
+                ..session
+            },
+        };
+
+        let event = session::Event::new(
+            &self.config.key(),
+            session::EventKind::Next {
+                next: next_driver.clone(),
+                before: before.clone(),
+                work_duration,
+            },
+            commit.clone(),
+        );
+        (session, vec![event])
+    }
+
+    fn next_driver(
+        &self,
+        session: &session::Session,
+        history: &session::History,
+    ) -> Result<Option<String>> {
+        let me = self.config.key();
+        let me = me.as_str();
+        let settings = session.settings.clone().unwrap_or_default();
+        let rotation = settings.rotation;
+        // The navigator was told they drive next
+        let announced = session.navigator.clone().filter(|navigator| {
+            settings.navigator == Navigator::NextDriver
+                && navigator != me
+                && session.drivers.present().contains(navigator)
+        });
+
+        match rotation {
+            Rotation::RoundRobin => Ok(session.drivers.next(me)),
+            Rotation::Pick => {
+                let drivers = session.drivers.present();
+                if drivers.len() < 2 {
+                    return Ok(session.drivers.next(me));
+                }
+
+                let default = announced
+                    .or_else(|| session.drivers.next(me))
+                    .and_then(|next| drivers.iter().position(|driver| driver == &next))
+                    .unwrap_or_default();
+                let names: Vec<String> = drivers
+                    .iter()
+                    .map(|driver| session.drivers.name(driver))
+                    .collect();
+                let drivers_strs: Vec<&str> = names.iter().map(|s| s.as_str()).collect();
+                let next = self.prompter.select_with_prompt(
+                    "Who should drive next?",
+                    &drivers_strs,
+                    default,
+                )?;
+                Ok(Some(drivers[next].clone()))
+            }
+            Rotation::Random | Rotation::LeastRecent => {
+                Ok(announced.or_else(|| rotation.next(&session.drivers, me, history)))
+            }
+        }
+    }
 }
@@ -25,18 +25,34 @@ impl<'a> Order<'a> {
             return Ok(());
         }
 
-        let drivers_strs: Vec<&str> = drivers.iter().map(|s| s.as_str()).collect();
+        let names: Vec<String> = drivers
+            .iter()
+            .map(|driver| session.drivers.name(driver))
+            .collect();
+        let drivers_strs: Vec<&str> = names.iter().map(|s| s.as_str()).collect();
         let order = self
             .prompter
             .sort("Use [space] and ↓↑ to move driver", &drivers_strs)?;
 
-        let ordered_drivers =
-            session::Drivers::new(order.into_iter().map(|i| drivers[i].clone()).collect());
+        let ordered_drivers = session.drivers.reorder(&order);
+
+        let rotation = session
+            .settings
+            .as_ref()
+            .map(|settings| settings.rotation)
+            .unwrap_or_default();
+        if rotation != session::Rotation::RoundRobin {
+            log::info!(
+                "Next driver is picked by '{}', the order is only used when showing drivers",
+                rotation.description()
+            );
+        }
 
-        let state = match session.state {
+        let state = match session.state.clone() {
+            // Only round robin depends on the order, others picked next at handover
             session::State::WaitingForNext {
                 next: Some(old_next),
-            } => {
+            } if rotation == session::Rotation::RoundRobin => {
                 // If we already are waiting for the next driver we potentially need to change iter
                 // to the next one in the new order
                 let previous_driver = drivers
@@ -53,7 +69,7 @@ impl<'a> Order<'a> {
                     .expect("Previous driver not found, this should not happen");
 
                 let next_driver = ordered_drivers.next(previous_driver.as_str());
-                let next_driver_name = next_driver.as_ref().unwrap();
+                let next_driver_name = ordered_drivers.name(next_driver.as_ref().unwrap());
 
                 let next_driver = if self
                     .prompter
@@ -61,8 +77,13 @@ impl<'a> Order<'a> {
                 {
                     next_driver
                 } else {
-                    let ordered = ordered_drivers.all();
-                    let ordered_strs: Vec<&str> = ordered.iter().map(|s| s.as_str()).collect();
+                    let ordered = ordered_drivers.present();
+                    let ordered_names: Vec<String> = ordered
+                        .iter()
+                        .map(|driver| ordered_drivers.name(driver))
+                        .collect();
+                    let ordered_strs: Vec<&str> =
+                        ordered_names.iter().map(|s| s.as_str()).collect();
                     let next = self.prompter.select_with_prompt(
                         "Who should be next?",
                         &ordered_strs,
@@ -72,20 +93,32 @@ impl<'a> Order<'a> {
                     Some(ordered[next].clone())
                 };
 
-                log::info!("Next driver: {}", next_driver.as_ref().unwrap());
+                log::info!(
+                    "Next driver: {}",
+                    ordered_drivers.name(next_driver.as_ref().unwrap())
+                );
 
                 session::State::WaitingForNext { next: next_driver }
             }
-            _ => session.state,
-        };
-
-        let session = session::Session {
-            drivers: ordered_drivers,
-            state,
-            ..session
+            _ => session.state.clone(),
         };
 
-        self.store.save(session)?;
+        let history = self.store.history()?;
+        let before = session.state.clone();
+        self.store.transition(session, &|session| {
+            session::ensure_unchanged(&before, &session)?;
+            anyhow::ensure!(
+                session.drivers.all() == drivers,
+                "the drivers changed, run 'mob order' again"
+            );
+            let session = session::Session {
+                drivers: session.drivers.reorder(&order),
+                state: state.clone(),
+                ..session
+            };
+            // The navigator depends on the order of drivers
+            Ok((session.with_navigator(&history), vec![]))
+        })?;
 
         Ok(())
     }
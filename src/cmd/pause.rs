@@ -0,0 +1,78 @@
+use crate::{config::Config, duration, session};
+use anyhow::Result;
+use chrono::{Local, Utc};
+use clap::Parser;
+use session::{Event, EventKind, State};
+
+#[derive(Parser, Debug)]
+pub struct PauseOpts {
+    /// Why the mob pauses, e.g. lunch
+    #[clap(name = "REASON")]
+    pub reason: Option<String>,
+
+    /// How long the pause is expected to last, e.g. 45m or 1h
+    #[clap(long = "for")]
+    pub duration: Option<String>,
+}
+
+pub struct Pause<'a> {
+    store: &'a dyn session::Store,
+    opts: PauseOpts,
+    config: Config,
+}
+
+impl<'a> Pause<'a> {
+    pub fn new(opts: PauseOpts, store: &'a impl session::Store, config: Config) -> Pause<'a> {
+        Self {
+            store,
+            opts,
+            config,
+        }
+    }
+
+    pub fn run(&self) -> Result<()> {
+        let session = self.store.load()?.identify(&self.config.driver());
+
+        if let State::Paused { .. } = session.state {
+            log::info!(
+                "The mob is already {}",
+                session.state.pause_description().unwrap_or_default()
+            );
+            return Ok(());
+        }
+
+        let until = match &self.opts.duration {
+            Some(duration) => Some(Utc::now() + duration::parse(duration)?),
+            None => None,
+        };
+
+        let session = self.store.transition(session, &|session| {
+            anyhow::ensure!(
+                !matches!(session.state, State::Paused { .. }),
+                "the mob is already paused"
+            );
+            let session = session::Session {
+                state: session.state.pause(self.opts.reason.clone(), until),
+                ..session
+            };
+            let event = Event::new(
+                &self.config.key(),
+                EventKind::Pause {
+                    reason: self.opts.reason.clone(),
+                },
+                None,
+            );
+            Ok((session, vec![event]))
+        })?;
+        let description = session.state.pause_description().unwrap_or_default();
+
+        log::info!(
+            "⏸️  The mob is {}, run 'mob resume' to continue",
+            description
+        );
+        if let Some(until) = until {
+            log::info!("See you at {}", until.with_timezone(&Local).format("%H:%M"));
+        }
+        Ok(())
+    }
+}
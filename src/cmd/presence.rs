@@ -0,0 +1,109 @@
+use crate::{config::Config, session};
+use anyhow::{Result, anyhow};
+use clap::Parser;
+use session::State;
+
+#[derive(Parser, Debug)]
+pub struct PresenceOpts {
+    /// Driver to mark, defaults to you
+    #[clap(name = "NAME")]
+    pub name: Option<String>,
+}
+
+pub struct Presence<'a> {
+    store: &'a dyn session::Store,
+    opts: PresenceOpts,
+    config: Config,
+    away: bool,
+}
+
+impl<'a> Presence<'a> {
+    /// Mark a driver as away, the rotation skips them until they are back
+    pub fn away(
+        opts: PresenceOpts,
+        store: &'a impl session::Store,
+        config: Config,
+    ) -> Presence<'a> {
+        Self {
+            store,
+            opts,
+            config,
+            away: true,
+        }
+    }
+
+    /// Mark a driver as back in the rotation
+    pub fn back(
+        opts: PresenceOpts,
+        store: &'a impl session::Store,
+        config: Config,
+    ) -> Presence<'a> {
+        Self {
+            store,
+            opts,
+            config,
+            away: false,
+        }
+    }
+
+    pub fn run(&self) -> Result<()> {
+        let session = self.store.load()?.identify(&self.config.driver());
+        let key = match &self.opts.name {
+            Some(name) => name.clone(),
+            None => self.config.key(),
+        };
+        // Others can be named by name, email or id
+        let name = match session.drivers.get(&key) {
+            Some(driver) => driver.key().to_string(),
+            None => return Err(anyhow!("{} is not part of the mob", key)),
+        };
+        let name = name.as_str();
+        let display = session.drivers.name(name);
+        if session.drivers.is_away(name) == self.away {
+            log::info!(
+                "{} is already {}",
+                display,
+                if self.away { "away" } else { "back" }
+            );
+            return Ok(());
+        }
+
+        if let State::Working { driver, .. } = session.state.active()
+            && self.away
+            && driver == name
+        {
+            log::warn!("{} is driving, run 'mob next' to hand over", display);
+        }
+
+        let history = self.store.history()?;
+        let session = self.store.transition(session, &|session| {
+            let drivers = session.drivers.set_away(name, self.away);
+
+            // Whoever was next should not have to wait for someone who is away
+            let state = if self.away {
+                session.state.replace_next(name, drivers.next(name))
+            } else {
+                session.state
+            };
+
+            let session = session::Session {
+                drivers,
+                state,
+                ..session
+            }
+            .with_navigator(&history);
+            Ok((session, vec![]))
+        })?;
+
+        if let State::WaitingForNext { next: Some(next) } = &session.state {
+            log::info!("Next driver: {}", session.drivers.name(next));
+        }
+
+        if self.away {
+            log::info!("{} is away, run 'mob back' to rejoin the rotation", display);
+        } else {
+            log::info!("{} is back in the rotation", display);
+        }
+        Ok(())
+    }
+}
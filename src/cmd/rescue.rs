@@ -0,0 +1,97 @@
+use crate::{git, prompt::Prompter};
+use anyhow::{Result, anyhow};
+use chrono::Local;
+use git::{SavedKind, SavedWork};
+
+pub struct Rescue<'a> {
+    git: &'a dyn git::Git,
+    prompter: &'a dyn Prompter,
+}
+
+impl<'a> Rescue<'a> {
+    pub fn new(git: &'a impl git::Git, prompter: &'a impl Prompter) -> Rescue<'a> {
+        Self { git, prompter }
+    }
+
+    pub fn run(&self) -> Result<()> {
+        let saved = self.git.saved_work()?;
+        if saved.is_empty() {
+            log::info!("mob has not set anything aside");
+            return Ok(());
+        }
+
+        let mut items = Vec::new();
+        for work in &saved {
+            items.push(format!(
+                "{}  {:<9}  {:<20}  {}",
+                work.time.with_timezone(&Local).format("%Y-%m-%d %H:%M"),
+                work.kind.description(),
+                work.branch.as_deref().unwrap_or("-"),
+                self.git.diffstat(&work.range())?
+            ));
+        }
+        items.push("Abort".to_string());
+        let items: Vec<&str> = items.iter().map(|item| item.as_str()).collect();
+        let selection =
+            self.prompter
+                .select_with_prompt("What do you want to restore?", &items, 0)?;
+        let Some(work) = saved.get(selection) else {
+            return Ok(());
+        };
+
+        if !self.git.tree_is_clean()? {
+            return Err(anyhow!(
+                "Working tree is dirty, commit or stash your changes first"
+            ));
+        }
+
+        let current = self
+            .git
+            .current_branch()?
+            .unwrap_or_else(|| "HEAD".to_string());
+        let onto_current = format!("Onto the current branch {}", current);
+        let selection = self.prompter.select_with_prompt(
+            "Where do you want to restore it?",
+            &[onto_current.as_str(), "Onto a new branch", "Abort"],
+            0,
+        )?;
+
+        match selection {
+            0 => self.restore(work)?,
+            1 => {
+                let default = format!("mob-rescue-{}", work.time.format("%Y%m%d-%H%M%S"));
+                let branch = self.prompter.input_string("Branch name", &default)?;
+                self.restore_on_branch(work, &branch)?
+            }
+            _ => return Ok(()),
+        }
+
+        log::info!("Restored, {} is kept until you remove it", work.reference);
+        Ok(())
+    }
+
+    /// Applies the saved work on top of what is checked out
+    fn restore(&self, work: &SavedWork) -> Result<()> {
+        match work.kind {
+            SavedKind::Stash => self.git.run(&["stash", "apply", &work.reference]),
+            SavedKind::Worktree => self
+                .git
+                .run(&["cherry-pick", "--no-commit", &work.reference]),
+            SavedKind::Branch => self.git.run(&["merge", "--no-edit", &work.reference]),
+        }
+    }
+
+    /// Restores the saved work on a new branch from where it was saved
+    fn restore_on_branch(&self, work: &SavedWork, branch: &str) -> Result<()> {
+        match work.kind {
+            SavedKind::Stash | SavedKind::Worktree => {
+                let base = format!("{}^1", work.reference);
+                self.git.run(&["switch", "--create", branch, &base])?;
+                self.restore(work)
+            }
+            SavedKind::Branch => self
+                .git
+                .run(&["switch", "--create", branch, &work.reference]),
+        }
+    }
+}
@@ -0,0 +1,52 @@
+use crate::{config::Config, session};
+use anyhow::Result;
+use chrono::Utc;
+use session::{Event, EventKind, State};
+
+pub struct Resume<'a> {
+    store: &'a dyn session::Store,
+    config: Config,
+}
+
+impl<'a> Resume<'a> {
+    pub fn new(store: &'a impl session::Store, config: Config) -> Resume<'a> {
+        Self { store, config }
+    }
+
+    pub fn run(&self) -> Result<()> {
+        let session = self.store.load()?.identify(&self.config.driver());
+
+        if !matches!(session.state, State::Paused { .. }) {
+            log::info!("The mob is not paused");
+            return Ok(());
+        }
+
+        let paused = session.state.clone();
+        let session = self.store.transition(session, &|session| {
+            session::ensure_unchanged(&paused, &session)?;
+            let session = session::Session {
+                state: session.state.resume(Utc::now()),
+                ..session
+            };
+            let event = Event::new(&self.config.key(), EventKind::Resume, None);
+            Ok((session, vec![event]))
+        })?;
+
+        match &session.state {
+            State::Working { driver, .. } => {
+                log::info!("▶️  {} continues driving", session.drivers.name(driver))
+            }
+            State::WaitingForNext { next: Some(next) }
+            | State::Break {
+                next: Some(next), ..
+            } => {
+                log::info!(
+                    "▶️  Waiting for {} to run 'mob start'",
+                    session.drivers.name(next)
+                )
+            }
+            _ => log::info!("▶️  Resumed, run 'mob start' to continue"),
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,208 @@
+use crate::{prompt::Prompter, session};
+use anyhow::{Result, anyhow};
+use chrono::NaiveTime;
+use clap::Parser;
+use console::style;
+use session::{DoneStrategy, Navigator, Rotation};
+
+#[derive(Parser, Debug, Default)]
+pub struct SettingsOpts {
+    /// Message of the commits made by mob next
+    #[clap(long)]
+    pub commit_message: Option<String>,
+
+    /// Minutes per turn
+    #[clap(long)]
+    pub work_duration: Option<i64>,
+
+    /// Mob minutes between breaks, 0 disables breaks
+    #[clap(long)]
+    pub break_interval: Option<i64>,
+
+    /// Minutes per break
+    #[clap(long)]
+    pub break_duration: Option<i64>,
+
+    /// When lunch starts as HH:MM, empty to remove
+    #[clap(long)]
+    pub lunch_start: Option<String>,
+
+    /// When lunch ends as HH:MM, empty to remove
+    #[clap(long)]
+    pub lunch_end: Option<String>,
+
+    /// Who navigates
+    #[clap(long, value_enum)]
+    pub navigator: Option<Navigator>,
+
+    /// How the next driver is picked
+    #[clap(long, value_enum)]
+    pub rotation: Option<Rotation>,
+
+    /// How mob done finishes the session
+    #[clap(long, value_enum)]
+    pub done_strategy: Option<DoneStrategy>,
+}
+
+impl SettingsOpts {
+    fn is_empty(&self) -> bool {
+        self.commit_message.is_none()
+            && self.work_duration.is_none()
+            && self.break_interval.is_none()
+            && self.break_duration.is_none()
+            && self.lunch_start.is_none()
+            && self.lunch_end.is_none()
+            && self.navigator.is_none()
+            && self.rotation.is_none()
+            && self.done_strategy.is_none()
+    }
+}
+
+pub struct Settings<'a> {
+    store: &'a dyn session::Store,
+    prompter: &'a dyn Prompter,
+    opts: SettingsOpts,
+}
+
+impl<'a> Settings<'a> {
+    pub fn new(
+        opts: SettingsOpts,
+        store: &'a impl session::Store,
+        prompter: &'a impl Prompter,
+    ) -> Settings<'a> {
+        Self {
+            store,
+            prompter,
+            opts,
+        }
+    }
+
+    pub fn run(&self) -> Result<()> {
+        let session = self.store.load_or_default()?;
+        let current = session.settings.clone().unwrap_or_default();
+
+        let edited = if self.opts.is_empty() {
+            Settings::print(&current);
+            if !self.prompter.confirm("Change settings?", false)? {
+                return Ok(());
+            }
+            let edited = current.clone().edit(self.prompter)?;
+            Settings::validate(&edited)?;
+            Some(edited)
+        } else {
+            None
+        };
+
+        let history = self.store.history()?;
+        let session = self.store.transition(session, &|session| {
+            let latest = session.settings.clone().unwrap_or_default();
+            let settings = match &edited {
+                // Everything was asked for, so it's only saved over what was shown
+                Some(edited) => {
+                    anyhow::ensure!(
+                        latest == current,
+                        "the settings changed, run 'mob settings' again"
+                    );
+                    edited.clone()
+                }
+                None => self.apply(latest),
+            };
+            Settings::validate(&settings)?;
+            let session = session::Session {
+                settings: Some(settings),
+                ..session
+            }
+            // The navigator setting may have changed
+            .with_navigator(&history);
+            Ok((session, vec![]))
+        })?;
+
+        log::info!("Settings saved, they apply from the next turn");
+        Settings::print(&session.settings.unwrap_or_default());
+        Ok(())
+    }
+
+    fn apply(&self, settings: session::Settings) -> session::Settings {
+        let lunch = |time: &Option<String>, current: Option<String>| match time {
+            Some(time) if time.is_empty() => None,
+            Some(time) => Some(time.clone()),
+            None => current,
+        };
+
+        session::Settings {
+            commit_message: self
+                .opts
+                .commit_message
+                .clone()
+                .unwrap_or(settings.commit_message),
+            work_duration: self.opts.work_duration.unwrap_or(settings.work_duration),
+            break_interval: self.opts.break_interval.unwrap_or(settings.break_interval),
+            break_duration: self.opts.break_duration.unwrap_or(settings.break_duration),
+            lunch_start: lunch(&self.opts.lunch_start, settings.lunch_start),
+            lunch_end: lunch(&self.opts.lunch_end, settings.lunch_end),
+            navigator: self.opts.navigator.unwrap_or(settings.navigator),
+            rotation: self.opts.rotation.unwrap_or(settings.rotation),
+            done_strategy: self.opts.done_strategy.unwrap_or(settings.done_strategy),
+            ..settings
+        }
+    }
+
+    fn validate(settings: &session::Settings) -> Result<()> {
+        if settings.work_duration <= 0 {
+            return Err(anyhow!("Work duration must be at least one minute"));
+        }
+        if settings.break_interval < 0 || settings.break_duration < 0 {
+            return Err(anyhow!("Break interval and duration can't be negative"));
+        }
+        for time in [&settings.lunch_start, &settings.lunch_end]
+            .into_iter()
+            .flatten()
+        {
+            NaiveTime::parse_from_str(time, "%H:%M")
+                .map_err(|_| anyhow!("Invalid lunch time '{}', use HH:MM", time))?;
+        }
+        if settings.lunch_start.is_some() != settings.lunch_end.is_some() {
+            return Err(anyhow!("Set both when lunch starts and ends"));
+        }
+        Ok(())
+    }
+
+    fn print(settings: &session::Settings) {
+        let lunch = match (&settings.lunch_start, &settings.lunch_end) {
+            (Some(start), Some(end)) => format!("{} - {}", start, end),
+            _ => "none".to_string(),
+        };
+        let breaks = if settings.break_interval > 0 {
+            format!(
+                "{} minutes every {} minutes",
+                settings.break_duration, settings.break_interval
+            )
+        } else {
+            "none".to_string()
+        };
+
+        println!("\n⚙️  Settings:");
+        println!(
+            "   Commit message: {}",
+            style(&settings.commit_message).bold()
+        );
+        println!(
+            "   Work duration:  {}",
+            style(format!("{} minutes", settings.work_duration)).bold()
+        );
+        println!("   Breaks:         {}", style(breaks).bold());
+        println!("   Lunch:          {}", style(lunch).bold());
+        println!(
+            "   Navigator:      {}",
+            style(settings.navigator.description()).bold()
+        );
+        println!(
+            "   Rotation:       {}",
+            style(settings.rotation.description()).bold()
+        );
+        println!(
+            "   Done:           {}",
+            style(settings.done_strategy.description()).bold()
+        );
+    }
+}
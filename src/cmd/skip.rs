@@ -0,0 +1,118 @@
+use crate::{command, config::Config, session};
+use anyhow::{Result, anyhow};
+use chrono::Utc;
+use session::{Event, EventKind, State};
+
+pub struct Skip<'a> {
+    store: &'a dyn session::Store,
+    config: Config,
+}
+
+impl<'a> Skip<'a> {
+    pub fn new(store: &'a impl session::Store, config: Config) -> Skip<'a> {
+        Self { store, config }
+    }
+
+    pub fn run(&self) -> Result<()> {
+        let me = self.config.key();
+        let me = me.as_str();
+        let session = self.store.load()?.identify(&self.config.driver());
+
+        match &session.state.clone().end_break(Utc::now()) {
+            State::WaitingForNext { next: Some(next) }
+            | State::Break {
+                next: Some(next), ..
+            } if next == me => self.skip(session),
+            State::WaitingForNext { next: Some(next) }
+            | State::Break {
+                next: Some(next), ..
+            } => {
+                log::warn!("It's {}s turn, not yours", session.drivers.name(next));
+                Ok(())
+            }
+            State::WaitingForNext { next: None } | State::Break { next: None, .. } => {
+                log::info!("Anyone can run start, there is no turn to skip");
+                Ok(())
+            }
+            State::Working { driver, .. } if driver == me => {
+                log::warn!("You are driving, run 'mob next' to hand over");
+                Ok(())
+            }
+            State::Working { driver, .. } => {
+                log::warn!("The current driver is {}", session.drivers.name(driver));
+                Ok(())
+            }
+            State::Stopped => {
+                log::warn!("No current mob session, run mob start");
+                Ok(())
+            }
+            paused @ State::Paused { .. } => {
+                log::warn!(
+                    "The mob is {}, run 'mob resume' first",
+                    paused.pause_description().unwrap_or_default()
+                );
+                Ok(())
+            }
+        }
+    }
+
+    fn skip(&self, session: session::Session) -> Result<()> {
+        let state = session.state.clone();
+        let history = self.store.history()?;
+        let session = self.store.transition(session, &|session| {
+            session::ensure_unchanged(&state, &session)?;
+            self.pass_turn(session, &history)
+        })?;
+
+        let next = match &session.state {
+            State::WaitingForNext { next: Some(next) }
+            | State::Break {
+                next: Some(next), ..
+            } => session.drivers.name(next),
+            _ => unreachable!("the turn was passed"),
+        };
+        let navigator = session
+            .navigator
+            .as_deref()
+            .map(|navigator| session.drivers.name(navigator));
+
+        log::info!("Next driver: {}", next);
+        if let Some(navigator) = &navigator {
+            log::info!("Navigator: {}", navigator);
+        }
+        command::run_hook(
+            &self.config.hooks.after_skip,
+            &self.config.name,
+            &next,
+            navigator.as_deref().unwrap_or_default(),
+        )
+    }
+
+    fn pass_turn(
+        &self,
+        session: session::Session,
+        history: &session::History,
+    ) -> Result<(session::Session, Vec<Event>)> {
+        let me = self.config.key();
+        let me = me.as_str();
+        let next = session
+            .drivers
+            .next(me)
+            .filter(|next| next != me)
+            .ok_or_else(|| anyhow!("There is nobody else to pass the turn to"))?;
+
+        let state = match session.state {
+            State::Break { until, .. } => State::Break {
+                next: Some(next.clone()),
+                until,
+            },
+            _ => State::WaitingForNext {
+                next: Some(next.clone()),
+            },
+        };
+        let session = session::Session { state, ..session }.with_navigator(history);
+
+        let event = Event::new(me, EventKind::Skip { next: Some(next) }, None);
+        Ok((session, vec![event]))
+    }
+}
@@ -1,7 +1,8 @@
-use crate::{command, config::Config, git, prompt::Prompter, session, timer};
+use crate::{command, config::Config, duration, git, prompt::Prompter, session, timer};
 use anyhow::{Result, anyhow};
+use chrono::{Local, Utc};
 use clap::Parser;
-use session::State;
+use session::{Event, EventKind, State};
 
 #[derive(Parser, Debug)]
 pub struct StartOpts {
@@ -36,20 +37,52 @@ impl<'a> Start<'a> {
     }
 
     pub fn run(&self) -> Result<()> {
-        let me = &self.config.name;
-        command::run_hook(&self.config.hooks.before_start, me, "")?;
+        let me = &self.config.key();
+        command::run_hook(&self.config.hooks.before_start, &self.config.name, "", "")?;
 
         self.is_clean()?;
 
-        let session = self.store.load_or_default()?;
+        let session = self
+            .store
+            .load_or_default()?
+            .identify(&self.config.driver());
+        let session = session::Session {
+            state: session.state.end_break(Utc::now()),
+            ..session
+        };
 
         match &session.state {
             State::Stopped => self.start_new(session)?,
-            State::Working { driver } if driver == me.as_str() => {
+            State::Working { driver, .. } if driver == me.as_str() => {
                 log::warn!("It's already your turn");
             }
-            State::Working { driver } => {
-                log::warn!("{} has not run mob next", driver);
+            State::Working { driver, .. } if session.state.lease_expired(Utc::now()) => {
+                log::info!(
+                    "{} was last seen {} ago, the turn seems abandoned",
+                    session.drivers.name(driver),
+                    self.last_seen(&session.state)
+                );
+                let selections = &["Take over", "Abort"];
+                let selection =
+                    self.prompter
+                        .select_with_prompt("What do you want to do?", selections, 0)?;
+
+                if selection == 0 {
+                    let take_over = self.event(EventKind::TakeOver {
+                        from: driver.clone(),
+                    });
+                    self.start(session, vec![take_over])?
+                }
+            }
+            State::Working { driver, .. } => {
+                log::warn!("{} has not run mob next", session.drivers.name(driver));
+                if session.state.last_seen(Utc::now()).is_some() {
+                    log::warn!(
+                        "{} was last seen {} ago",
+                        session.drivers.name(driver),
+                        self.last_seen(&session.state)
+                    );
+                }
                 let selections = &["Retry", "Take turn with the risk of losing work"];
                 let selection =
                     self.prompter
@@ -57,20 +90,37 @@ impl<'a> Start<'a> {
 
                 match selection {
                     0 => return self.run(),
-                    _ => self.start(session)?,
+                    _ => {
+                        let take_over = self.event(EventKind::TakeOver {
+                            from: driver.clone(),
+                        });
+                        self.start(session, vec![take_over])?
+                    }
                 }
             }
             State::WaitingForNext { next: Some(driver) } if driver == me.as_str() => {
-                self.start(session)?;
+                self.start(session, vec![])?;
             }
-            State::WaitingForNext { next: None } => self.start(session)?,
+            State::WaitingForNext { next: None } => self.start(session, vec![])?,
             State::WaitingForNext { next: Some(driver) } => {
-                if session.drivers.contains(self.config.name.as_str()) {
+                if session.drivers.contains(me) {
                     self.take_over(driver, session.clone())?;
                 } else {
-                    self.start(session)?;
+                    self.start(session, vec![])?;
                 }
             }
+            State::Break { until, .. } => {
+                log::warn!(
+                    "The mob is on a break until {}, come back then",
+                    until.with_timezone(&Local).format("%H:%M")
+                );
+            }
+            paused @ State::Paused { .. } => {
+                log::warn!(
+                    "The mob is {}, run 'mob resume' first",
+                    paused.pause_description().unwrap_or_default()
+                );
+            }
         };
 
         Ok(())
@@ -84,50 +134,86 @@ impl<'a> Start<'a> {
 
         log::warn!("Working tree is dirty:\n{}", status);
 
-        let selection = self
-            .prompter
-            .select(&["Quit", "Stash changes", "Discard changes"], 0)?;
+        let selection = self.prompter.select(
+            &[
+                "Quit",
+                "Stash changes",
+                "Discard changes, ignored files are kept",
+            ],
+            0,
+        )?;
 
         match selection {
             0 => Err(anyhow!("Working tree is not clean")),
-            1 => self.git.run(&["stash", "--include-untracked"]),
-            2 => self
-                .git
-                .run(&["reset", "HEAD", "--hard"])
-                .and_then(|_| self.git.run(&["clean", "-fdx"])),
+            1 => self.git.run(&[
+                "stash",
+                "push",
+                "--include-untracked",
+                "--message",
+                git::STASH_MESSAGE,
+            ]),
+            2 => {
+                self.git.backup_worktree()?;
+                self.git.run(&["reset", "HEAD", "--hard"])?;
+                // Ignored files aren't backed up, so they aren't removed
+                self.git.run(&["clean", "-fd"])
+            }
             _ => unreachable!("could not come here"),
         }
     }
 
     fn take_over(&self, from: &str, session: session::Session) -> Result<()> {
-        let take_and_remove = format!("Take turn and remove {} from the mob", from);
+        let name = session.drivers.name(from);
+        let take_and_remove = format!("Take turn and remove {} from the mob", name);
         let selections = &["Take turn", take_and_remove.as_str(), "Abort"];
         let selection = self.prompter.select_with_prompt(
-            &format!("It's {}s turn. What do you want to do?", from),
+            &format!("It's {}s turn. What do you want to do?", name),
             selections,
             0,
         )?;
 
         match selection {
-            0 => self.start(session),
+            0 => {
+                let take_over = self.event(EventKind::TakeOver {
+                    from: from.to_string(),
+                });
+                self.start(session, vec![take_over])
+            }
             1 => {
-                let session = session::Session {
-                    drivers: session.drivers.remove(from),
-                    state: State::WaitingForNext {
-                        next: Some(self.config.name.clone()),
-                    },
-                    ..session
-                };
-                self.start(session)
+                let remove = self.event(EventKind::Remove {
+                    removed: from.to_string(),
+                });
+                self.start(session, vec![remove])
             }
             _ => Ok(()),
         }
     }
 
-    fn start(&self, session: session::Session) -> Result<()> {
+    fn last_seen(&self, state: &State) -> String {
+        state
+            .last_seen(Utc::now())
+            .map(|last_seen| duration::format(last_seen).human())
+            .unwrap_or_default()
+    }
+
+    fn event(&self, kind: EventKind) -> Event {
+        Event::new(&self.config.key(), kind, None)
+    }
+
+    fn start(&self, session: session::Session, events: Vec<Event>) -> Result<()> {
+        let me = self.config.key();
         self.git.run(&["fetch", "--all", "--prune"])?;
 
         let remote_branches = session.branches.with_remote(&self.config.remote);
+        // Local commits that never made it to the remote would be lost
+        if self.git.has_branch(session.branches.branch.as_str())?
+            && self.git.count_commits(
+                remote_branches.branch.as_str(),
+                session.branches.branch.as_str(),
+            )? > 0
+        {
+            self.git.backup(session.branches.branch.as_str())?;
+        }
         self.git.run(&[
             "switch",
             "--force-create",
@@ -135,31 +221,95 @@ impl<'a> Start<'a> {
             remote_branches.branch.as_str(),
         ])?;
 
-        let previous_driver = session.get_driver();
-
-        let session = session::Session {
-            state: State::Working {
-                driver: self.config.name.clone(),
-            },
-            drivers: session
-                .drivers
-                .insert(previous_driver, self.config.name.as_str()),
-            ..session
-        };
-
-        let next_driver = session.drivers.next(self.config.name.as_str());
-        let work_duration = session.settings.as_ref().unwrap().work_duration;
-
-        self.store.save(session)?;
+        let work_duration = self.work_duration(session.settings.as_ref().unwrap());
+        let state = session.state.clone();
+        let commit = self.git.commit_id("HEAD")?;
+        let history = self.store.history()?;
+
+        let session = self.store.transition(session, &|session| {
+            let session = session::Session {
+                state: session.state.end_break(Utc::now()),
+                ..session
+            };
+            session::ensure_unchanged(&state, &session)?;
+
+            let previous_driver = session.get_driver();
+            // Drivers removed when taking over leave as part of the turn change
+            let drivers = events
+                .iter()
+                .fold(session.drivers, |drivers, event| match &event.kind {
+                    EventKind::Remove { removed } if drivers.contains(removed) => {
+                        drivers.remove(removed)
+                    }
+                    _ => drivers,
+                });
+
+            let session = session::Session {
+                state: State::Working {
+                    driver: me.clone(),
+                    started: Utc::now(),
+                    work_duration,
+                    last_seen: Some(Utc::now()),
+                },
+                drivers: drivers
+                    .insert(previous_driver, self.config.driver())
+                    .set_away(&me, false),
+                ..session
+            };
+            let session = session::Session {
+                navigator: session.navigator_for(&me, &history),
+                ..session
+            };
+
+            let mut events = events.clone();
+            events.push(Event::new(&me, EventKind::Start, commit.clone()));
+            Ok((session, events))
+        })?;
+
+        let settings = session.settings.clone().unwrap_or_default();
+        let next_driver = match (settings.rotation, settings.navigator) {
+            (session::Rotation::RoundRobin, _) => session.drivers.next(&me),
+            // The navigator drives next
+            (_, session::Navigator::NextDriver) => session.navigator.clone(),
+            // Decided at handover
+            _ => None,
+        }
+        .map(|next| session.drivers.name(&next));
+        let navigator = session
+            .navigator
+            .as_ref()
+            .map(|navigator| session.drivers.name(navigator));
 
         let next_driver_name = next_driver.unwrap_or_else(|| String::from("anyone"));
-        self.start_timer(work_duration, &next_driver_name)
+        self.start_timer(work_duration, &next_driver_name, navigator)
+    }
+
+    /// Two sessions on one branch would overwrite each other's work
+    fn ensure_branch_unused(&self, branch: &str) -> Result<()> {
+        if git::store::session_name(branch).is_some() {
+            return Err(anyhow!(
+                "{} holds mob session data, pick another branch",
+                branch
+            ));
+        }
+        let other = self.store.load_all()?.into_iter().find(|(_, session)| {
+            session.branches.branch == branch && !matches!(session.state, State::Stopped)
+        });
+        match other {
+            Some((name, _)) => Err(anyhow!(
+                "The session {} is using {}, pick another branch",
+                name,
+                branch
+            )),
+            None => Ok(()),
+        }
     }
 
     fn start_new(&self, session: session::Session) -> Result<()> {
+        let me = self.config.key();
         let previous_driver = session.get_driver();
 
-        let settings = match session.settings {
+        let settings = match session.settings.clone() {
             Some(settings) => settings,
             None => session::Settings::ask(self.prompter)?,
         };
@@ -169,11 +319,12 @@ impl<'a> Start<'a> {
                 .git
                 .current_branch()
                 .unwrap_or(None)
-                .unwrap_or(session.branches.base_branch),
-            ..session.branches
+                .unwrap_or(session.branches.base_branch.clone()),
+            ..session.branches.clone()
         };
 
         let branches = session::Branches::ask(self.prompter, default_branches)?;
+        self.ensure_branch_unused(&branches.branch)?;
 
         let remote_branches = branches.with_remote(&self.config.remote);
 
@@ -185,6 +336,16 @@ impl<'a> Start<'a> {
             remote_branches.base_branch
         );
 
+        // Without a remote the base branch is handed over in the bundles too
+        if self.config.bundle_dir.is_some() && !self.git.has_branch(&remote_branches.base_branch)? {
+            self.git.run(&[
+                "push",
+                "--no-verify",
+                self.config.remote.as_str(),
+                branches.base_branch.as_str(),
+            ])?;
+        }
+
         if !self.git.has_branch(&remote_branches.base_branch)? {
             return Err(anyhow!(
                 "You need to push your branch `{}` first",
@@ -198,20 +359,43 @@ impl<'a> Start<'a> {
 
         self.setup_branch(&branches, &remote_branches)?;
 
-        let session = session::Session {
-            state: State::Working {
-                driver: self.config.name.clone(),
-            },
-            drivers: session
-                .drivers
-                .insert(previous_driver, self.config.name.as_str()),
-            settings: Some(settings),
-            branches,
-        };
-
-        self.store.save(session.clone())?;
-
-        self.start_timer(session.settings.unwrap().work_duration, "anyone")
+        let work_duration = self.work_duration(&settings);
+        let commit = self.git.commit_id("HEAD")?;
+        let history = self.store.history()?;
+
+        let session = self.store.transition(session, &|session| {
+            // Someone else may have started a session meanwhile
+            session::ensure_unchanged(&State::Stopped, &session)?;
+
+            let session = session::Session {
+                state: State::Working {
+                    driver: me.clone(),
+                    started: Utc::now(),
+                    work_duration,
+                    last_seen: Some(Utc::now()),
+                },
+                drivers: session
+                    .drivers
+                    .insert(previous_driver.clone(), self.config.driver())
+                    .set_away(&me, false),
+                settings: Some(settings.clone()),
+                branches: branches.clone(),
+                mob_time: 0,
+                ..session
+            };
+            let session = session::Session {
+                navigator: session.navigator_for(&me, &history),
+                ..session
+            };
+            let start = Event::new(&me, EventKind::Start, commit.clone());
+            Ok((session, vec![start]))
+        })?;
+        let navigator = session
+            .navigator
+            .as_ref()
+            .map(|navigator| session.drivers.name(navigator));
+
+        self.start_timer(work_duration, "anyone", navigator)
     }
 
     fn setup_branch(
@@ -252,11 +436,12 @@ impl<'a> Start<'a> {
                     }
 
                     1 => {
-                        self.git.run(&["branch", "-D", branches.branch.as_str()])?;
+                        self.delete_branch(branches.branch.as_str())?;
                         self.git.run(&["checkout", branches.branch.as_str()])?;
                     }
                     _ => {
-                        self.git.run(&["branch", "-D", branches.branch.as_str()])?;
+                        self.delete_branch(branches.branch.as_str())?;
+                        self.git.backup(remote_branches.branch.as_str())?;
                         self.git.run(&[
                             "push",
                             &self.config.remote,
@@ -287,7 +472,7 @@ impl<'a> Start<'a> {
                     ])?;
                     self.git.run(&["checkout", branches.branch.as_str()])?;
                 } else {
-                    self.git.run(&["branch", "-D", branches.branch.as_str()])?;
+                    self.delete_branch(branches.branch.as_str())?;
 
                     create_and_push()?;
                 }
@@ -305,6 +490,7 @@ impl<'a> Start<'a> {
                         self.git.run(&["checkout", branches.branch.as_str()])?;
                     }
                     _ => {
+                        self.git.backup(remote_branches.branch.as_str())?;
                         self.git.run(&[
                             "push",
                             &self.config.remote,
@@ -324,13 +510,66 @@ impl<'a> Start<'a> {
         Ok(())
     }
 
-    fn start_timer(&self, minutes: i64, next_driver: &str) -> Result<()> {
-        let minutes = self.opts.minutes.unwrap_or(minutes);
+    fn delete_branch(&self, branch: &str) -> Result<()> {
+        self.git.backup(branch)?;
+        self.git.run(&["branch", "-D", branch])
+    }
 
+    fn work_duration(&self, settings: &session::Settings) -> i64 {
+        self.opts.minutes.unwrap_or(settings.work_duration)
+    }
+
+    fn start_timer(
+        &self,
+        minutes: i64,
+        next_driver: &str,
+        navigator: Option<String>,
+    ) -> Result<()> {
         let current_driver = self.config.name.as_str();
-        command::run_hook(&self.config.hooks.after_start, current_driver, next_driver)?;
-        timer::start("Your turn", chrono::Duration::minutes(minutes))?;
+        let navigator = match navigator {
+            Some(navigator) => {
+                log::info!("Navigator: {}", navigator);
+                navigator
+            }
+            None => String::new(),
+        };
+
+        command::run_hook(
+            &self.config.hooks.after_start,
+            current_driver,
+            next_driver,
+            &navigator,
+        )?;
+        if self.config.dry_run {
+            log::info!("Would start a {} minute timer", minutes);
+            return Ok(());
+        }
+        timer::start(
+            "Your turn",
+            chrono::Duration::minutes(minutes),
+            chrono::Duration::minutes(session::HEARTBEAT_INTERVAL),
+            &mut || heartbeat(self.store, &self.config.key()),
+        )?;
         log::info!("Done. Run mob next");
-        command::run_hook(&self.config.hooks.after_timer, current_driver, next_driver)
+        command::run_hook(
+            &self.config.hooks.after_timer,
+            current_driver,
+            next_driver,
+            &navigator,
+        )
+    }
+}
+
+/// Lets the others know `me` is still driving
+pub(super) fn heartbeat(store: &dyn session::Store, me: &str) {
+    let result = store.load().and_then(|session| match session.state {
+        State::Working { ref driver, .. } if driver == me => store.save(session::Session {
+            state: session.state.heartbeat(me, Utc::now()),
+            ..session
+        }),
+        _ => Ok(()),
+    });
+    if let Err(err) = result {
+        log::trace!("Failed to refresh lease: {}", err);
     }
 }
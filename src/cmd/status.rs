@@ -1,5 +1,6 @@
-use crate::{config::Config, session};
+use crate::{config::Config, duration, git, session};
 use anyhow::Result;
+use chrono::{Duration, Local, Utc};
 use clap::Parser;
 use console::style;
 use session::State;
@@ -9,25 +10,40 @@ pub struct StatusOpts {
     /// Show raw status
     #[clap(short, long)]
     raw: bool,
+
+    /// Show all sessions in this repository
+    #[clap(short, long)]
+    all: bool,
 }
 
 pub struct Status<'a> {
+    git: &'a dyn git::Git,
     store: &'a dyn session::Store,
     config: Config,
     opts: StatusOpts,
 }
 
 impl<'a> Status<'a> {
-    pub fn new(opts: StatusOpts, store: &'a impl session::Store, config: Config) -> Status<'a> {
+    pub fn new(
+        opts: StatusOpts,
+        git: &'a impl git::Git,
+        store: &'a impl session::Store,
+        config: Config,
+    ) -> Status<'a> {
         Self {
             opts,
+            git,
             store,
             config,
         }
     }
 
     pub fn run(&self) -> Result<()> {
-        let session = self.store.load_or_default()?;
+        if self.opts.all {
+            return self.print_all();
+        }
+
+        let session = self.store.load_read_only()?;
 
         if self.opts.raw {
             println!("{:#?}", session);
@@ -40,29 +56,60 @@ impl<'a> Status<'a> {
         Ok(())
     }
 
+    fn print_all(&self) -> Result<()> {
+        let sessions = self.store.load_all()?;
+        if sessions.is_empty() {
+            log::info!("No sessions, run 'mob start' to start one");
+        }
+
+        for (name, session) in sessions {
+            println!("\n📋 Session {}", style(&name).bold());
+            if self.opts.raw {
+                println!("{:#?}", session);
+                continue;
+            }
+            self.print_status(&session);
+            Status::print_drivers(&session);
+        }
+        Ok(())
+    }
+
     fn print_status(&self, session: &session::Session) {
-        let me = self.config.name.clone();
-        match &session.state {
+        if let Some(version) = &session.newer_version {
+            println!(
+                "⚠️  {}",
+                style(format!(
+                    "Session written by a newer version of mob ({}), upgrade to change it",
+                    version
+                ))
+                .yellow()
+            );
+        }
+        let me = self.me(session);
+        match &session.state.clone().end_break(Utc::now()) {
             State::Stopped => {
                 let help = "Run 'mob start' to start a new session";
                 println!("✋ {}", style("Stopped").red());
                 println!("   {}", style(help).cyan());
             }
-            State::Working { driver } => {
+            State::Working { driver, .. } => {
                 let driver = if driver == &me {
                     "You are".to_string()
                 } else {
-                    format!("{} is", driver)
+                    format!("{} is", session.drivers.name(driver))
                 };
                 println!("🚗 {} {}", driver, style("driving").green(),);
+                self.print_navigator(session);
+                Status::print_turn_time(&session.state);
+                Status::print_last_seen(&session.state);
                 println!("   {}", style("Run 'mob next' when finished").cyan());
-                Status::print_branches(&session.branches);
+                self.print_branches(&session.branches);
             }
             State::WaitingForNext { next } => {
                 let next = match next {
-                    Some(driver) if driver == &me => "You",
-                    Some(driver) => driver,
-                    None => "Anyone",
+                    Some(driver) if driver == &me => "You".to_string(),
+                    Some(driver) => session.drivers.name(driver),
+                    None => "Anyone".to_string(),
                 };
 
                 println!(
@@ -70,17 +117,130 @@ impl<'a> Status<'a> {
                     style("Waiting").blue(),
                     next
                 );
-                Status::print_branches(&session.branches);
+                self.print_navigator(session);
+                self.print_branches(&session.branches);
             }
+            State::Break { next, until } => {
+                let next = match next {
+                    Some(driver) if driver == &me => "you".to_string(),
+                    Some(driver) => session.drivers.name(driver),
+                    None => "anyone".to_string(),
+                };
+
+                println!(
+                    "☕ {} until {}, then {} can run 'mob start'",
+                    style("Break").magenta(),
+                    until.with_timezone(&Local).format("%H:%M"),
+                    next
+                );
+                self.print_branches(&session.branches);
+            }
+            paused @ State::Paused {
+                since, previous, ..
+            } => {
+                println!(
+                    "⏸️  Mob {} since {}",
+                    style(paused.pause_description().unwrap_or_default()).yellow(),
+                    since.with_timezone(&Local).format("%H:%M")
+                );
+                match previous.active() {
+                    State::Working { driver, .. } => {
+                        println!("   {} was driving", session.drivers.name(driver))
+                    }
+                    State::WaitingForNext { next: Some(next) }
+                    | State::Break {
+                        next: Some(next), ..
+                    } => println!("   {} is next", session.drivers.name(next)),
+                    _ => {}
+                }
+                println!("   {}", style("Run 'mob resume' to continue").cyan());
+                self.print_branches(&session.branches);
+            }
+        }
+    }
+
+    /// The key of this user in the session, which may still be a plain name
+    fn me(&self, session: &session::Session) -> String {
+        match session.drivers.find(&self.config.driver()) {
+            Some(driver) => driver.key().to_string(),
+            None => self.config.key(),
+        }
+    }
+
+    fn print_navigator(&self, session: &session::Session) {
+        let navigator = match &session.navigator {
+            Some(navigator) if navigator == &self.me(session) => "You are".to_string(),
+            Some(navigator) => format!("{} is", session.drivers.name(navigator)),
+            None => return,
+        };
+        println!("🧭 {} {}", navigator, style("navigating").yellow());
+    }
+
+    fn print_turn_time(state: &State) {
+        let (started, time_left) = match (state, state.time_left(Utc::now())) {
+            (State::Working { started, .. }, Some(time_left)) => (started, time_left),
+            _ => return,
+        };
+
+        let elapsed = duration::format(Utc::now() - *started).human();
+        if time_left >= Duration::zero() {
+            let left = duration::format(time_left).human();
+            println!("   Started {} ago, {} left", elapsed, style(left).green());
+        } else {
+            let overdue = duration::format(-time_left).human();
+            println!(
+                "   Started {} ago, {}",
+                elapsed,
+                style(format!("{} overdue", overdue)).red().bold()
+            );
+        }
+    }
+
+    fn print_last_seen(state: &State) {
+        let now = Utc::now();
+        let last_seen = match state.last_seen(now) {
+            Some(last_seen) => duration::format(last_seen).human(),
+            None => return,
+        };
+
+        if state.lease_expired(now) {
+            println!(
+                "   {}",
+                style(format!(
+                    "Driver last seen {} ago, anyone can take over with 'mob start'",
+                    last_seen
+                ))
+                .red()
+            );
+        } else {
+            println!("   Driver last seen {} ago", last_seen);
         }
     }
 
-    fn print_branches(branches: &session::Branches) {
+    fn print_branches(&self, branches: &session::Branches) {
         println!(
             "\n🚚 working on {} with parent {}",
             style(&branches.branch).red().bold(),
             style(&branches.base_branch).cyan().bold(),
-        )
+        );
+
+        // As of the last fetch, remote branches may be missing
+        let remote_branches = branches.with_remote(&self.config.remote);
+        let behind = self
+            .git
+            .count_commits(&remote_branches.branch, &remote_branches.base_branch);
+        match behind {
+            Ok(0) => {}
+            Ok(behind) => println!(
+                "   {}",
+                style(format!(
+                    "{} commits behind {}, run 'mob sync-base' to catch up",
+                    behind, branches.base_branch
+                ))
+                .yellow()
+            ),
+            Err(err) => log::trace!("Could not compare with base branch: {}", err),
+        }
     }
 
     fn print_drivers(session: &session::Session) {
@@ -89,22 +249,48 @@ impl<'a> Status<'a> {
             return;
         }
 
-        let current = match &session.state {
-            State::Working { driver } => Some(driver),
+        let current = match session.state.active() {
+            State::Working { driver, .. } => Some(driver),
             State::WaitingForNext {
                 next: Some(next), ..
+            }
+            | State::Break {
+                next: Some(next), ..
             } => Some(next),
             _ => None,
         };
 
         println!("\n👯 Drivers:");
         for driver in session.drivers.all() {
-            let prefix = match current {
-                Some(name) if name == &driver => "›",
+            let prefix = match (current, &session.navigator) {
+                (Some(name), _) if name == &driver => "›",
+                (_, Some(name)) if name == &driver => "·",
                 _ => " ",
             };
 
-            println!(" {} {}", style(prefix).red(), driver);
+            if session.drivers.is_away(&driver) {
+                println!(
+                    " {} {}",
+                    style(prefix).red(),
+                    style(format!("{} (away)", session.drivers.name(&driver))).dim()
+                );
+            } else {
+                println!(" {} {}", style(prefix).red(), session.drivers.name(&driver));
+            }
+        }
+
+        let rotation = session
+            .settings
+            .as_ref()
+            .map(|settings| settings.rotation)
+            .unwrap_or_default();
+        match rotation {
+            session::Rotation::RoundRobin => {}
+            session::Rotation::Random => println!("\n🎲 Next driver is picked at random"),
+            session::Rotation::LeastRecent => {
+                println!("\n⏳ Next driver is whoever drove least recently")
+            }
+            session::Rotation::Pick => println!("\n👉 Next driver is picked at handover"),
         }
     }
 }
@@ -0,0 +1,139 @@
+use crate::{config::Config, git, session};
+use anyhow::{Result, anyhow};
+use clap::Parser;
+use session::{Event, EventKind, State};
+
+#[derive(Parser, Debug, Default)]
+pub struct SyncBaseOpts {
+    /// Rebase the mob branch instead of merging, set `sync_rebase` in the
+    /// config to always rebase
+    #[clap(long)]
+    pub rebase: bool,
+}
+
+pub struct SyncBase<'a> {
+    git: &'a dyn git::Git,
+    store: &'a dyn session::Store,
+    opts: SyncBaseOpts,
+    config: Config,
+}
+
+impl<'a> SyncBase<'a> {
+    pub fn new(
+        opts: SyncBaseOpts,
+        git: &'a impl git::Git,
+        store: &'a impl session::Store,
+        config: Config,
+    ) -> SyncBase<'a> {
+        Self {
+            git,
+            store,
+            opts,
+            config,
+        }
+    }
+
+    pub fn run(&self) -> Result<()> {
+        let me = self.config.key();
+        let session = self.store.load()?.identify(&self.config.driver());
+
+        match &session.state {
+            State::Working { driver, .. } if driver == me.as_str() => self.sync(session)?,
+            State::Working { driver, .. } => {
+                log::warn!(
+                    "{} is driving, only the driver can sync the base branch",
+                    session.drivers.name(driver)
+                );
+            }
+            paused @ State::Paused { .. } => {
+                log::warn!(
+                    "The mob is {}, run 'mob resume' first",
+                    paused.pause_description().unwrap_or_default()
+                );
+            }
+            State::Stopped => log::warn!("No current mob session, run mob start"),
+            State::WaitingForNext { .. } | State::Break { .. } => {
+                log::warn!("Only the driver can sync the base branch, run 'mob start' first");
+            }
+        }
+        Ok(())
+    }
+
+    fn sync(&self, session: session::Session) -> Result<()> {
+        let branches = session.branches.clone();
+        let remote_branches = branches.with_remote(&self.config.remote);
+
+        if self.git.current_branch()?.as_deref() != Some(branches.branch.as_str()) {
+            return Err(anyhow!(
+                "You must be on branch {} to sync the base branch",
+                branches.branch
+            ));
+        }
+
+        self.git.run(&["fetch", self.config.remote.as_str()])?;
+        let behind = self
+            .git
+            .count_commits(&branches.branch, &remote_branches.base_branch)?;
+        if behind == 0 {
+            log::info!(
+                "{} is up to date with {}",
+                branches.branch,
+                branches.base_branch
+            );
+            return Ok(());
+        }
+
+        // Only committed work can be merged or rebased, it's pushed below
+        if !self.git.tree_is_clean()? {
+            self.git.run(&["add", "--all"])?;
+            self.git.run(&[
+                "commit",
+                "--message",
+                session.settings.as_ref().unwrap().commit_message.as_str(),
+                "--no-verify",
+            ])?;
+        }
+
+        if self.opts.rebase || self.config.sync_rebase {
+            self.git.run(&["rebase", &remote_branches.base_branch])?;
+            // The branch is only rewritten by the driver
+            self.git.run(&[
+                "push",
+                "--no-verify",
+                "--force-with-lease",
+                self.config.remote.as_str(),
+                branches.branch.as_str(),
+            ])?;
+        } else {
+            self.git
+                .run(&["merge", "--no-edit", &remote_branches.base_branch])?;
+            self.git.run(&[
+                "push",
+                "--no-verify",
+                self.config.remote.as_str(),
+                branches.branch.as_str(),
+            ])?;
+        }
+
+        let state = session.state.clone();
+        let commit = self.git.commit_id("HEAD")?;
+        self.store.transition(session, &|session| {
+            session::ensure_unchanged(&state, &session)?;
+            let event = Event::new(
+                &self.config.key(),
+                EventKind::SyncBase { commits: behind },
+                commit.clone(),
+            );
+            Ok((session, vec![event]))
+        })?;
+
+        log::info!(
+            "Brought {} commit{} from {} into {}",
+            behind,
+            if behind == 1 { "" } else { "s" },
+            remote_branches.base_branch,
+            branches.branch
+        );
+        Ok(())
+    }
+}
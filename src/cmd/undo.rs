@@ -0,0 +1,174 @@
+use super::start::heartbeat;
+use crate::{config::Config, git, session, timer};
+use anyhow::{Result, anyhow};
+use chrono::Utc;
+use session::{Event, EventKind, State};
+
+pub struct Undo<'a> {
+    git: &'a dyn git::Git,
+    store: &'a dyn session::Store,
+    config: Config,
+}
+
+impl<'a> Undo<'a> {
+    pub fn new(git: &'a impl git::Git, store: &'a impl session::Store, config: Config) -> Undo<'a> {
+        Self { git, store, config }
+    }
+
+    pub fn run(&self) -> Result<()> {
+        let me = self.config.key();
+        let session = self.store.load()?.identify(&self.config.driver());
+        let history = self.store.history()?;
+
+        // Only the handover is undone, once someone starts it's their turn
+        let handover = match history.events().last() {
+            Some(
+                event @ Event {
+                    driver,
+                    kind: EventKind::Next { .. },
+                    ..
+                },
+            ) if *driver == me => event.clone(),
+            _ => {
+                log::warn!(
+                    "Nothing to undo, only your handover can be undone before anyone starts"
+                );
+                return Ok(());
+            }
+        };
+        if !matches!(
+            session.state,
+            State::WaitingForNext { .. } | State::Break { .. }
+        ) {
+            log::warn!("Nothing to undo, the mob is not waiting for the next driver");
+            return Ok(());
+        }
+
+        // The turn that was handed over
+        let turn = history
+            .turns()
+            .pop()
+            .filter(|turn| turn.driver == me && turn.ended == Some(handover.time))
+            .ok_or_else(|| anyhow!("Can't find the start of your turn in the history"))?;
+
+        let branch = session.branches.branch.clone();
+        if self.git.current_branch()?.as_deref() != Some(branch.as_str()) {
+            return Err(anyhow!("You must be on branch {} to undo", branch));
+        }
+        let head = self.git.commit_id("HEAD")?;
+        if head != handover.commit {
+            return Err(anyhow!(
+                "{} has changed since you handed over, undo it with git",
+                branch
+            ));
+        }
+
+        // Bring back uncommitted what next committed, older handovers don't
+        // say what that was so their commits are kept
+        let (before, turn_duration) = match &handover.kind {
+            EventKind::Next {
+                before,
+                work_duration,
+                ..
+            } => (before.clone(), *work_duration),
+            _ => (None, None),
+        };
+        let reset = match (&before, &head) {
+            (Some(before), Some(head)) if before != head => Some((before, head)),
+            _ => None,
+        };
+        if let Some((before, head)) = reset {
+            self.git.run(&["reset", "--mixed", before])?;
+            if let Err(err) = self.push(&branch, head) {
+                self.git.run(&["reset", "--mixed", head])?;
+                return Err(err);
+            }
+        }
+
+        let state = session.state.clone();
+        let transition = self.store.transition(session, &|session| {
+            session::ensure_unchanged(&state, &session)?;
+            let work_duration = turn_duration.unwrap_or_else(|| {
+                session
+                    .settings
+                    .as_ref()
+                    .map(|settings| settings.work_duration)
+                    .unwrap_or_default()
+            });
+            let mob_time = match session.state {
+                // Give back the time next added, a break that started counts as taken
+                State::WaitingForNext { .. } => {
+                    session.mob_time - (handover.time - turn.started).num_minutes()
+                }
+                _ => session.mob_time,
+            };
+            let session = session::Session {
+                state: State::Working {
+                    driver: me.clone(),
+                    started: turn.started,
+                    work_duration,
+                    last_seen: Some(Utc::now()),
+                },
+                mob_time: mob_time.max(0),
+                ..session
+            }
+            .with_navigator(&history);
+            let event = Event::new(&me, EventKind::Undo, before.clone().or(head.clone()));
+            Ok((session, vec![event]))
+        });
+        let session = match transition {
+            Ok(session) => session,
+            Err(err) => {
+                // Put the handed over work back where the next driver expects it
+                if let Some((before, head)) = reset {
+                    self.git.run(&["reset", "--mixed", head])?;
+                    self.push(&branch, before)?;
+                }
+                return Err(err.into());
+            }
+        };
+
+        log::info!("Your turn again, the handover was undone");
+        if let Some(navigator) = &session.navigator {
+            log::info!("Navigator: {}", session.drivers.name(navigator));
+        }
+        self.continue_timer(&session.state)
+    }
+
+    /// Counts down what is left of the turn, keeping the lease alive
+    fn continue_timer(&self, state: &State) -> Result<()> {
+        let State::Working {
+            started,
+            work_duration,
+            ..
+        } = state
+        else {
+            return Ok(());
+        };
+        let left = (*started + chrono::Duration::minutes(*work_duration)) - Utc::now();
+        if self.config.dry_run {
+            log::info!("Would start a {} minute timer", left.num_minutes().max(0));
+            return Ok(());
+        }
+        timer::start(
+            "Your turn",
+            left.max(chrono::Duration::zero()),
+            chrono::Duration::minutes(session::HEARTBEAT_INTERVAL),
+            &mut || heartbeat(self.store, &self.config.key()),
+        )?;
+        log::info!("Done. Run mob next");
+        Ok(())
+    }
+
+    /// Moves the remote branch from `from` to HEAD, unless someone else moved it
+    fn push(&self, branch: &str, from: &str) -> Result<()> {
+        let lease = format!("--force-with-lease={}:{}", branch, from);
+        self.git.run(&[
+            "push",
+            "--no-verify",
+            &lease,
+            self.config.remote.as_str(),
+            branch,
+        ])
+    }
+}
@@ -2,7 +2,7 @@ use anyhow::Error;
 use anyhow::Result;
 use anyhow::anyhow;
 use std::borrow::Cow;
-use std::ffi::OsStr;
+use std::ffi::{OsStr, OsString};
 use std::fmt;
 use std::io;
 use std::path::{Path, PathBuf};
@@ -61,6 +61,7 @@ impl fmt::Display for OutputError {
 pub struct Command<'name> {
     name: Cow<'name, Path>,
     working_directory: Option<PathBuf>,
+    envs: Vec<(OsString, OsString)>,
 }
 
 impl<'name> Command<'name> {
@@ -69,6 +70,7 @@ impl<'name> Command<'name> {
         Command {
             name,
             working_directory: None,
+            envs: Vec::new(),
         }
     }
 
@@ -82,6 +84,7 @@ impl<'name> Command<'name> {
         if let Some(working_directory) = self.working_directory.as_ref() {
             cmd.current_dir(working_directory);
         }
+        cmd.envs(self.envs.iter().map(|(key, value)| (key, value)));
 
         cmd
     }
@@ -89,11 +92,17 @@ impl<'name> Command<'name> {
     /// Configure the working directory of this command.
     pub fn working_directory<'p>(self, path: impl Into<&'p Path>) -> Self {
         Command {
-            name: self.name,
             working_directory: Some(path.into().to_owned()),
+            ..self
         }
     }
 
+    /// Set an environment variable for the command.
+    pub fn env(mut self, key: impl Into<OsString>, value: impl Into<OsString>) -> Self {
+        self.envs.push((key.into(), value.into()));
+        self
+    }
+
     /// Run the given command, return a string of all output.
     pub fn run_stdout<S>(&self, args: impl IntoIterator<Item = S>) -> Result<String, Error>
     where
@@ -180,7 +189,12 @@ impl<'name> Command<'name> {
     }
 }
 
-pub fn run_hook(hook: &Option<String>, current_driver: &str, next_driver: &str) -> Result<()> {
+pub fn run_hook(
+    hook: &Option<String>,
+    current_driver: &str,
+    next_driver: &str,
+    navigator: &str,
+) -> Result<()> {
     let cmd = if let Some(cmd) = hook {
         cmd
     } else {
@@ -189,6 +203,7 @@ pub fn run_hook(hook: &Option<String>, current_driver: &str, next_driver: &str)
 
     let cmd = cmd.replace(config::VAR_CURRENT_DRIVER, current_driver);
     let cmd = cmd.replace(config::VAR_NEXT_DRIVER, next_driver);
+    let cmd = cmd.replace(config::VAR_NAVIGATOR, navigator);
 
     let sh = Command::new(os::command("sh"));
     sh.run_checked(["-c", cmd.as_str()])
@@ -1,3 +1,6 @@
+use crate::command::Command;
+use crate::os;
+use crate::session::Driver;
 use anyhow::{Result, anyhow};
 use confy;
 use dialoguer::{Confirm, Input};
@@ -12,6 +15,7 @@ const CONFIG_FILE: &str = ".mob";
 
 pub const VAR_NEXT_DRIVER: &str = "NEXT_DRIVER";
 pub const VAR_CURRENT_DRIVER: &str = "CURRENT_DRIVER";
+pub const VAR_NAVIGATOR: &str = "NAVIGATOR";
 
 const AFTER_TIMER_MESSAGE: &str = "mob next NEXT_DRIVER";
 
@@ -22,6 +26,7 @@ pub struct Hooks {
     pub after_timer: Option<String>,
     pub before_next: Option<String>,
     pub after_next: Option<String>,
+    pub after_skip: Option<String>,
     pub before_done: Option<String>,
     pub after_done: Option<String>,
 }
@@ -34,6 +39,7 @@ impl Hooks {
             after_timer,
             before_next: None,
             after_next: None,
+            after_skip: None,
             before_done: None,
             after_done: None,
         }
@@ -43,8 +49,28 @@ impl Hooks {
 #[derive(Serialize, Deserialize)]
 pub struct Config {
     pub name: String,
+    /// Tells drivers with the same name apart, defaults to `git config user.email`
+    #[serde(default)]
+    pub email: Option<String>,
+    /// Stable id used instead of the email if set
+    #[serde(default)]
+    pub id: Option<String>,
     pub remote: String,
+    /// Keep sessions in this directory instead of pushing them to the
+    /// remote, e.g. a synced folder or a local path for a single machine
+    #[serde(default)]
+    pub meta_dir: Option<PathBuf>,
+    /// Hand over through git bundles in this directory instead of pushing
+    /// to the remote, for mobs without a shared remote
+    #[serde(default)]
+    pub bundle_dir: Option<PathBuf>,
+    /// Rebase the mob branch in `mob sync-base` instead of merging
+    #[serde(default)]
+    pub sync_rebase: bool,
     pub hooks: Hooks,
+    /// Set by --dry-run, nothing should be changed
+    #[serde(skip)]
+    pub dry_run: bool,
 }
 
 impl Config {
@@ -66,10 +92,26 @@ impl Config {
 
         Ok(Config {
             name,
+            email: None,
+            id: None,
             remote,
+            meta_dir: None,
+            bundle_dir: None,
+            sync_rebase: false,
             hooks,
+            dry_run: false,
         })
     }
+
+    /// You as a driver
+    pub fn driver(&self) -> Driver {
+        Driver::new(&self.name, self.email.clone(), self.id.clone())
+    }
+
+    /// Identifies you in the session state and history
+    pub fn key(&self) -> String {
+        self.driver().key().to_string()
+    }
 }
 
 fn ask_after_timer() -> Option<String> {
@@ -138,8 +180,14 @@ impl Default for Config {
     fn default() -> Self {
         Self {
             name: "".to_string(),
+            email: None,
+            id: None,
             remote: DEFAULT_REMOTE.to_string(),
+            meta_dir: None,
+            bundle_dir: None,
+            sync_rebase: false,
             hooks: Hooks::new(None),
+            dry_run: false,
         }
     }
 }
@@ -160,11 +208,25 @@ pub fn load() -> Result<Config> {
         )
     })?;
 
-    if config.name.is_empty() {
+    let config = if config.name.is_empty() {
         let config = Config::ask()?;
         confy::store_path(&path, &config)?;
         log::info!("Stored config to {}", &path_str);
-        return Ok(config);
-    }
-    Ok(config)
+        config
+    } else {
+        config
+    };
+
+    Ok(Config {
+        email: config.email.or_else(git_email),
+        ..config
+    })
+}
+
+fn git_email() -> Option<String> {
+    Command::new(os::command("git"))
+        .run_stdout(["config", "user.email"])
+        .ok()
+        .map(|email| email.trim().to_string())
+        .filter(|email| !email.is_empty())
 }
@@ -1,3 +1,6 @@
+use anyhow::{Result, anyhow};
+use chrono::Utc;
+
 pub struct FormattedDuration(chrono::Duration);
 
 impl FormattedDuration {
@@ -34,3 +37,67 @@ impl FormattedDuration {
 pub fn format(duration: chrono::Duration) -> FormattedDuration {
     FormattedDuration(duration)
 }
+
+/// Parse durations like `45`, `45m`, `2h` or `1d`, plain numbers are minutes
+pub fn parse(duration: &str) -> Result<chrono::Duration> {
+    let duration = duration.trim();
+    let (number, unit) = match duration.find(|c: char| !c.is_ascii_digit()) {
+        Some(index) => duration.split_at(index),
+        None => (duration, "m"),
+    };
+    let number: i64 = number.parse().map_err(|_| {
+        anyhow!(
+            "Invalid duration '{}', use for example 45m, 2h or 1d",
+            duration
+        )
+    })?;
+
+    let minutes = match unit {
+        "m" => 1,
+        "h" => 60,
+        "d" => 24 * 60,
+        _ => {
+            return Err(anyhow!(
+                "Invalid duration '{}', use for example 45m, 2h or 1d",
+                duration
+            ));
+        }
+    };
+    // Durations are counted from now, so they must keep a valid time
+    let now = Utc::now();
+    number
+        .checked_mul(minutes * 60 * 1000)
+        .map(chrono::Duration::milliseconds)
+        .filter(|duration| {
+            now.checked_add_signed(*duration).is_some()
+                && now.checked_sub_signed(*duration).is_some()
+        })
+        .ok_or_else(|| anyhow!("Duration '{}' is too long", duration))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_units() {
+        assert_eq!(parse("45").unwrap(), chrono::Duration::minutes(45));
+        assert_eq!(parse("45m").unwrap(), chrono::Duration::minutes(45));
+        assert_eq!(parse("2h").unwrap(), chrono::Duration::hours(2));
+        assert_eq!(parse("1d").unwrap(), chrono::Duration::days(1));
+    }
+
+    #[test]
+    fn parse_invalid() {
+        assert!(parse("").is_err());
+        assert!(parse("h").is_err());
+        assert!(parse("2w").is_err());
+    }
+
+    #[test]
+    fn parse_too_long() {
+        assert!(parse("99999999999999d").is_err());
+        assert!(parse("9223372036854775807m").is_err());
+        assert!(parse("99999999d").is_err());
+    }
+}
@@ -0,0 +1,423 @@
+use super::file_store::FileStore;
+use super::store::{self, Error, SESSION_FILENAME, Store};
+use super::{Git, GitCommand, SavedWork};
+use anyhow::{Context, anyhow};
+use std::cell::RefCell;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Our copy of the refs in the newest bundle
+const SHARED_REFS: &str = "refs/mob/shared";
+const EXTENSION: &str = "bundle";
+/// Older bundles are removed, someone may still be reading them
+const KEEP_BUNDLES: usize = 5;
+
+/// Hands over through git bundles in a shared directory instead of a
+/// remote. Pushing writes a bundle with the mob branches and sessions,
+/// fetching imports the newest one into the remote tracking branches.
+pub struct Bundle {
+    git: GitCommand,
+    dir: PathBuf,
+    /// Newest bundle as last imported, pushing imports newer ones first
+    imported: RefCell<Option<String>>,
+}
+
+impl Bundle {
+    pub fn new(git: GitCommand, dir: PathBuf) -> Self {
+        Self {
+            git,
+            dir,
+            imported: RefCell::new(None),
+        }
+    }
+
+    fn newest(&self) -> Result<Option<String>, Error> {
+        Ok(self.bundles()?.pop())
+    }
+
+    /// Names of the bundles, oldest first. They are numbered instead of
+    /// timestamped, clocks on different machines may disagree.
+    fn bundles(&self) -> Result<Vec<String>, Error> {
+        let entries = match fs::read_dir(&self.dir) {
+            Ok(entries) => entries,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(err) => {
+                return Err(anyhow!(err)
+                    .context(format!("Could not list {}", self.dir.display()))
+                    .into());
+            }
+        };
+        let mut bundles: Vec<String> = entries
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| path.extension().is_some_and(|ext| ext == EXTENSION))
+            .filter_map(|path| Some(path.file_name()?.to_str()?.to_string()))
+            .filter(|name| sequence(name).is_some())
+            .collect();
+        bundles.sort_by_key(|name| sequence(name));
+        Ok(bundles)
+    }
+
+    /// Name and commit of every shared ref
+    fn shared_refs(&self) -> Result<Vec<(String, String)>, Error> {
+        let output = self.git.command.run_stdout([
+            "for-each-ref",
+            "--format=%(refname:strip=3)%00%(objectname)",
+            SHARED_REFS,
+        ])?;
+        Ok(output
+            .lines()
+            .filter_map(|line| line.split_once('\0'))
+            .map(|(name, oid)| (name.to_string(), oid.to_string()))
+            .collect())
+    }
+
+    /// Name and commit of every ref in the newest bundle. Only their
+    /// commits are fetched, no ref is updated.
+    fn peek(&self) -> Result<Vec<(String, String)>, Error> {
+        let _lock = FileStore::lock(&self.dir, false)?;
+        let Some(newest) = self.newest()? else {
+            return Ok(Vec::new());
+        };
+        let path = path_str(&self.dir.join(newest))?;
+        let heads = self
+            .git
+            .command
+            .run_stdout(["bundle", "list-heads", &path])?;
+        let refs: Vec<(String, String)> = heads
+            .lines()
+            .filter_map(|line| {
+                let (oid, name) = line.split_once(' ')?;
+                let name = name.strip_prefix(SHARED_REFS)?.strip_prefix('/')?;
+                Some((name.to_string(), oid.to_string()))
+            })
+            .collect();
+
+        let mut args = vec!["fetch".to_string(), "--quiet".to_string(), path];
+        args.extend(
+            refs.iter()
+                .map(|(name, _)| format!("{}/{}", SHARED_REFS, name)),
+        );
+        if !refs.is_empty() {
+            self.git.command.run_checked(&args)?;
+        }
+        Ok(refs)
+    }
+
+    /// The shared refs, imported first unless the session is read only
+    fn current_refs(&self) -> Result<Vec<(String, String)>, Error> {
+        if self.git.read_only {
+            return self.peek();
+        }
+        self.import()?;
+        self.shared_refs()
+    }
+
+    fn shared_ref(&self, name: &str) -> Result<Option<String>, Error> {
+        Ok(self
+            .shared_refs()?
+            .into_iter()
+            .find(|(shared, _)| shared == name)
+            .map(|(_, oid)| oid))
+    }
+
+    fn import(&self) -> Result<(), Error> {
+        let _lock = FileStore::lock(&self.dir, false)?;
+        self.import_locked()
+    }
+
+    fn import_locked(&self) -> Result<(), Error> {
+        let Some(newest) = self.newest()? else {
+            return Ok(());
+        };
+        if self.imported.borrow().as_ref() == Some(&newest) {
+            return Ok(());
+        }
+
+        let before = self.shared_refs()?;
+        let path = self.dir.join(&newest);
+        let refspec = format!("+{0}/*:{0}/*", SHARED_REFS);
+        self.git
+            .run_quietly(&["fetch", "--quiet", "--prune", &path_str(&path)?, &refspec])
+            .with_context(|| format!("Could not import {}", path.display()))?;
+
+        let after = self.shared_refs()?;
+        for (name, oid) in &after {
+            self.git
+                .run_quietly(&["update-ref", &self.tracking_ref(name), oid])?;
+        }
+        for (name, _) in before
+            .iter()
+            .filter(|(name, _)| !after.iter().any(|(shared, _)| shared == name))
+        {
+            self.git
+                .run_quietly(&["update-ref", "-d", &self.tracking_ref(name)])?;
+        }
+
+        log::info!("Imported {}", newest);
+        self.imported.replace(Some(newest));
+        Ok(())
+    }
+
+    /// Sets or deletes shared refs and writes them to a new bundle. Like a
+    /// push it's rejected if someone changed one of the refs since we saw it.
+    fn publish(&self, updates: &[(String, Option<String>)]) -> Result<(), Error> {
+        let _lock = FileStore::lock(&self.dir, true)?;
+
+        let mut expected = Vec::new();
+        for (name, _) in updates {
+            expected.push(self.shared_ref(name)?);
+        }
+        self.import_locked()?;
+        for ((name, _), expected) in updates.iter().zip(expected) {
+            if self.shared_ref(name)? != expected {
+                return Err(Error::Conflict);
+            }
+        }
+
+        for (name, oid) in updates {
+            let shared = format!("{}/{}", SHARED_REFS, name);
+            let tracking = self.tracking_ref(name);
+            match oid {
+                Some(oid) => {
+                    self.git.run_quietly(&["update-ref", &shared, oid])?;
+                    self.git.run_quietly(&["update-ref", &tracking, oid])?;
+                }
+                None => {
+                    self.git.run_quietly(&["update-ref", "-d", &shared])?;
+                    self.git.run_quietly(&["update-ref", "-d", &tracking])?;
+                }
+            }
+        }
+
+        let refs: Vec<String> = self
+            .shared_refs()?
+            .into_iter()
+            .map(|(name, _)| format!("{}/{}", SHARED_REFS, name))
+            .collect();
+        let bundles = self.bundles()?;
+        let stale = if refs.is_empty() {
+            // Nothing left to share
+            self.imported.replace(None);
+            bundles.as_slice()
+        } else {
+            let next = bundles.last().and_then(|name| sequence(name)).unwrap_or(0) + 1;
+            let name = format!("{}.{}", next, EXTENSION);
+            self.write(&name, &refs)?;
+            self.imported.replace(Some(name));
+            &bundles[..bundles.len().saturating_sub(KEEP_BUNDLES - 1)]
+        };
+        for bundle in stale {
+            fs::remove_file(self.dir.join(bundle))
+                .unwrap_or_else(|err| log::trace!("Failed to remove {}: {}", bundle, err));
+        }
+        Ok(())
+    }
+
+    fn write(&self, name: &str, refs: &[String]) -> Result<(), Error> {
+        let path = self.dir.join(name);
+        FileStore::replace(&path, |tmp| {
+            let mut args = vec![
+                "bundle".to_string(),
+                "create".to_string(),
+                "--quiet".to_string(),
+                path_str(tmp)?,
+            ];
+            args.extend(refs.iter().cloned());
+            self.git.command.run_checked(&args)?;
+            Ok(())
+        })?;
+        log::info!("Wrote {}", path.display());
+        Ok(())
+    }
+
+    fn tracking_ref(&self, name: &str) -> String {
+        format!("refs/remotes/{}/{}", self.git.remote, name)
+    }
+
+    fn push(&self, args: &[&str]) -> anyhow::Result<()> {
+        let delete = args.contains(&"--delete");
+        // The first argument is the remote, the bundle directory takes its place
+        let refspecs = args.iter().filter(|arg| !arg.starts_with('-')).skip(1);
+
+        let mut updates = Vec::new();
+        for refspec in refspecs {
+            let (src, dst) = refspec.split_once(':').unwrap_or((refspec, refspec));
+            let oid = if delete {
+                None
+            } else {
+                Some(
+                    self.git
+                        .commit_id(src)?
+                        .ok_or_else(|| anyhow!("{} does not exist", src))?,
+                )
+            };
+            updates.push((dst.to_string(), oid));
+        }
+        self.publish(&updates).map_err(|err| match err {
+            Error::Conflict => anyhow!(
+                "push rejected, someone else changed {} in {}",
+                updates
+                    .iter()
+                    .map(|(name, _)| name.as_str())
+                    .collect::<Vec<_>>()
+                    .join(", "),
+                self.dir.display()
+            ),
+            err => err.into(),
+        })
+    }
+
+    /// Points the local session branch at the shared one, returns its commit
+    fn sync_meta(&self, meta_branch: &str) -> Result<String, Error> {
+        let oid = self
+            .current_refs()?
+            .into_iter()
+            .find(|(shared, _)| shared == meta_branch)
+            .map(|(_, oid)| oid)
+            .ok_or_else(|| Error::Missing {
+                source: anyhow!("No session in {}", self.dir.display()),
+            })?;
+        if !self.git.read_only {
+            let local = format!("refs/heads/{}", meta_branch);
+            self.git.run_quietly(&["update-ref", &local, &oid])?;
+        }
+        Ok(oid)
+    }
+}
+
+/// Sequence number of a bundle, one more than the newest when written
+fn sequence(name: &str) -> Option<u64> {
+    name.strip_suffix(EXTENSION)?
+        .strip_suffix('.')?
+        .parse()
+        .ok()
+}
+
+fn path_str(path: &Path) -> anyhow::Result<String> {
+    path.to_str()
+        .map(|path| path.to_string())
+        .ok_or_else(|| anyhow!("{} is not valid unicode", path.display()))
+}
+
+impl Git for Bundle {
+    fn run(&self, args: &[&str]) -> anyhow::Result<()> {
+        match args.first() {
+            Some(&"fetch") => Ok(self.import()?),
+            Some(&"push") => self.push(&args[1..]),
+            _ => self.git.run(args),
+        }
+    }
+
+    fn tree_is_clean(&self) -> anyhow::Result<bool> {
+        self.git.tree_is_clean()
+    }
+
+    fn has_branch(&self, branch: &str) -> anyhow::Result<bool> {
+        self.git.has_branch(branch)
+    }
+
+    fn current_branch(&self) -> anyhow::Result<Option<String>> {
+        self.git.current_branch()
+    }
+
+    fn dirty_files(&self) -> anyhow::Result<String> {
+        self.git.dirty_files()
+    }
+
+    fn commit_id(&self, rev: &str) -> anyhow::Result<Option<String>> {
+        self.git.commit_id(rev)
+    }
+
+    fn changed_files(&self, from: &str, to: &str) -> anyhow::Result<Vec<String>> {
+        self.git.changed_files(from, to)
+    }
+
+    fn count_commits(&self, from: &str, to: &str) -> anyhow::Result<usize> {
+        self.git.count_commits(from, to)
+    }
+
+    fn authors(&self, from: &str, to: &str) -> anyhow::Result<Vec<(String, String)>> {
+        self.git.authors(from, to)
+    }
+
+    fn git_path(&self, name: &str) -> anyhow::Result<PathBuf> {
+        self.git.git_path(name)
+    }
+
+    fn backup(&self, rev: &str) -> anyhow::Result<Option<String>> {
+        self.git.backup(rev)
+    }
+
+    fn backup_worktree(&self) -> anyhow::Result<Option<String>> {
+        self.git.backup_worktree()
+    }
+
+    fn saved_work(&self) -> anyhow::Result<Vec<SavedWork>> {
+        self.git.saved_work()
+    }
+
+    fn diffstat(&self, range: &str) -> anyhow::Result<String> {
+        self.git.diffstat(range)
+    }
+}
+
+impl Store for Bundle {
+    fn session(&self) -> &str {
+        &self.git.session
+    }
+
+    fn load(&self) -> Result<Vec<u8>, store::Error> {
+        let oid = self.sync_meta(&self.git.meta_branch())?;
+        self.git
+            .show_file(&oid, SESSION_FILENAME)
+            .map_err(|err| Error::Missing {
+                source: anyhow!("Could not read session data: {}", err),
+            })
+    }
+
+    fn save(&self, data: &[u8], history: &[u8]) -> Result<(), store::Error> {
+        let oid = self.git.commit_meta(data, history)?;
+        self.publish(&[(self.git.meta_branch(), Some(oid))])
+    }
+
+    fn load_history(&self) -> Result<Vec<u8>, store::Error> {
+        let oid = self.sync_meta(&self.git.meta_branch())?;
+        Ok(self.git.history_at(&oid))
+    }
+
+    fn list(&self) -> Result<Vec<(String, Vec<u8>)>, store::Error> {
+        let mut sessions = Vec::new();
+        for (meta_branch, oid) in self.current_refs()? {
+            if let Some(name) = store::session_name(&meta_branch) {
+                sessions.push((name, self.git.show_file(&oid, SESSION_FILENAME)?));
+            }
+        }
+        Ok(sessions)
+    }
+
+    fn clean(&self) -> Result<(), store::Error> {
+        let meta_branch = self.git.meta_branch();
+        self.git
+            .run_quietly(&["branch", "-D", &meta_branch])
+            .unwrap_or_else(|err| log::trace!("Failed to delete local branch: {}", err));
+        self.publish(&[(meta_branch, None)])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn numbered_bundles() {
+        assert_eq!(sequence("9.bundle"), Some(9));
+        assert_eq!(sequence("10.bundle"), Some(10));
+        assert_eq!(sequence("10.tmp"), None);
+        assert_eq!(sequence("20211010-142241.bundle"), None);
+
+        let mut names = vec!["10.bundle", "9.bundle", "11.bundle"];
+        names.sort_by_key(|name| sequence(name));
+        assert_eq!(names, vec!["9.bundle", "10.bundle", "11.bundle"]);
+    }
+}
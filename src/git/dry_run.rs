@@ -0,0 +1,76 @@
+use super::{Git, SavedWork};
+use anyhow::Result;
+use std::path::PathBuf;
+
+/// Logs the git commands that would change anything instead of running
+/// them, queries still run against the repository
+pub struct DryRun<'a> {
+    git: &'a dyn Git,
+}
+
+impl<'a> DryRun<'a> {
+    pub fn new(git: &'a impl Git) -> DryRun<'a> {
+        Self { git }
+    }
+}
+
+impl Git for DryRun<'_> {
+    fn run(&self, args: &[&str]) -> Result<()> {
+        log::info!("Would run: git {}", args.join(" "));
+        Ok(())
+    }
+
+    fn tree_is_clean(&self) -> Result<bool> {
+        self.git.tree_is_clean()
+    }
+
+    fn has_branch(&self, branch: &str) -> Result<bool> {
+        self.git.has_branch(branch)
+    }
+
+    fn current_branch(&self) -> Result<Option<String>> {
+        self.git.current_branch()
+    }
+
+    fn dirty_files(&self) -> Result<String> {
+        self.git.dirty_files()
+    }
+
+    fn commit_id(&self, rev: &str) -> Result<Option<String>> {
+        self.git.commit_id(rev)
+    }
+
+    fn changed_files(&self, from: &str, to: &str) -> Result<Vec<String>> {
+        self.git.changed_files(from, to)
+    }
+
+    fn count_commits(&self, from: &str, to: &str) -> Result<usize> {
+        self.git.count_commits(from, to)
+    }
+
+    fn authors(&self, from: &str, to: &str) -> Result<Vec<(String, String)>> {
+        self.git.authors(from, to)
+    }
+
+    fn git_path(&self, name: &str) -> Result<PathBuf> {
+        self.git.git_path(name)
+    }
+
+    fn backup(&self, rev: &str) -> Result<Option<String>> {
+        log::info!("Would back up {}", rev);
+        Ok(None)
+    }
+
+    fn backup_worktree(&self) -> Result<Option<String>> {
+        log::info!("Would back up the working tree");
+        Ok(None)
+    }
+
+    fn saved_work(&self) -> Result<Vec<SavedWork>> {
+        self.git.saved_work()
+    }
+
+    fn diffstat(&self, range: &str) -> Result<String> {
+        self.git.diffstat(range)
+    }
+}
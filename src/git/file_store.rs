@@ -0,0 +1,236 @@
+use super::store::{self, DEFAULT_SESSION, Error, HISTORY_FILENAME, SESSION_FILENAME, Store};
+use anyhow::{Context, anyhow};
+use std::cell::RefCell;
+use std::fs::{self, File, OpenOptions};
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+
+const LOCK_FILENAME: &str = "lock";
+
+/// Keeps sessions in a shared directory instead of the `mob-meta`
+/// branch, one directory per session
+pub struct FileStore {
+    dir: PathBuf,
+    session: String,
+    /// Session data as last loaded, saving fails if someone changed it since
+    loaded: RefCell<Option<Vec<u8>>>,
+}
+
+impl FileStore {
+    pub fn new(dir: PathBuf) -> Self {
+        Self {
+            dir,
+            session: DEFAULT_SESSION.to_string(),
+            loaded: RefCell::new(None),
+        }
+    }
+
+    /// Keep the session in a directory of its own, named after it
+    pub fn with_session(self, session: Option<String>) -> Self {
+        Self {
+            session: session.unwrap_or(self.session),
+            ..self
+        }
+    }
+
+    fn session_dir(&self) -> PathBuf {
+        self.dir.join(&self.session)
+    }
+
+    /// Locks the session directory until the returned file is dropped
+    pub(super) fn lock(dir: &Path, exclusive: bool) -> Result<File, Error> {
+        fs::create_dir_all(dir).with_context(|| format!("Could not create {}", dir.display()))?;
+        let path = dir.join(LOCK_FILENAME);
+        let file = OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(&path)
+            .with_context(|| format!("Could not open {}", path.display()))?;
+        if exclusive {
+            file.lock()
+        } else {
+            file.lock_shared()
+        }
+        .with_context(|| format!("Could not lock {}", path.display()))?;
+        Ok(file)
+    }
+
+    fn read(path: &Path) -> Result<Option<Vec<u8>>, Error> {
+        match fs::read(path) {
+            Ok(data) => Ok(Some(data)),
+            Err(err) if err.kind() == ErrorKind::NotFound => Ok(None),
+            Err(err) => Err(anyhow!(err)
+                .context(format!("Could not read {}", path.display()))
+                .into()),
+        }
+    }
+
+    fn write(path: &Path, data: &[u8]) -> Result<(), Error> {
+        FileStore::replace(path, |tmp| Ok(fs::write(tmp, data)?))
+    }
+
+    /// Lets `write` create a file next to `path` and moves it in place, so
+    /// readers never see half of it
+    pub(super) fn replace(
+        path: &Path,
+        write: impl FnOnce(&Path) -> anyhow::Result<()>,
+    ) -> Result<(), Error> {
+        let tmp = path.with_extension("tmp");
+        write(&tmp)
+            .and_then(|_| Ok(fs::rename(&tmp, path)?))
+            .with_context(|| format!("Could not write {}", path.display()))?;
+        Ok(())
+    }
+
+    fn missing(dir: &Path) -> Error {
+        Error::Missing {
+            source: anyhow!("No session in {}", dir.display()),
+        }
+    }
+}
+
+impl Store for FileStore {
+    fn session(&self) -> &str {
+        &self.session
+    }
+
+    fn load(&self) -> Result<Vec<u8>, store::Error> {
+        let dir = self.session_dir();
+        if !dir.exists() {
+            return Err(FileStore::missing(&dir));
+        }
+        let _lock = FileStore::lock(&dir, false)?;
+
+        let data = FileStore::read(&dir.join(SESSION_FILENAME))?
+            .ok_or_else(|| FileStore::missing(&dir))?;
+        self.loaded.replace(Some(data.clone()));
+        Ok(data)
+    }
+
+    fn save(&self, data: &[u8], history: &[u8]) -> Result<(), store::Error> {
+        let dir = self.session_dir();
+        let _lock = FileStore::lock(&dir, true)?;
+
+        let path = dir.join(SESSION_FILENAME);
+        if FileStore::read(&path)? != *self.loaded.borrow() {
+            return Err(Error::Conflict);
+        }
+
+        let history_path = dir.join(HISTORY_FILENAME);
+        let mut full_history = FileStore::read(&history_path)?.unwrap_or_default();
+        full_history.extend_from_slice(history);
+        FileStore::write(&history_path, &full_history)?;
+        FileStore::write(&path, data)?;
+
+        self.loaded.replace(Some(data.to_vec()));
+        Ok(())
+    }
+
+    fn load_history(&self) -> Result<Vec<u8>, store::Error> {
+        let dir = self.session_dir();
+        if !dir.exists() {
+            return Err(FileStore::missing(&dir));
+        }
+        let _lock = FileStore::lock(&dir, false)?;
+
+        Ok(FileStore::read(&dir.join(HISTORY_FILENAME))?.unwrap_or_default())
+    }
+
+    fn list(&self) -> Result<Vec<(String, Vec<u8>)>, store::Error> {
+        let entries = match fs::read_dir(&self.dir) {
+            Ok(entries) => entries,
+            Err(err) if err.kind() == ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(err) => {
+                return Err(anyhow!(err)
+                    .context(format!("Could not list {}", self.dir.display()))
+                    .into());
+            }
+        };
+
+        let mut sessions = Vec::new();
+        for entry in entries.flatten() {
+            let path = entry.path().join(SESSION_FILENAME);
+            if let (Some(name), Some(data)) = (
+                entry.file_name().to_str(),
+                FileStore::read(&path).ok().flatten(),
+            ) {
+                sessions.push((name.to_string(), data));
+            }
+        }
+        sessions.sort();
+        Ok(sessions)
+    }
+
+    fn clean(&self) -> Result<(), store::Error> {
+        let dir = self.session_dir();
+        if let Err(err) = fs::remove_dir_all(&dir) {
+            log::trace!("Failed to remove {}: {}", dir.display(), err);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn missing_session() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = FileStore::new(dir.path().to_path_buf());
+
+        assert!(matches!(store.load(), Err(Error::Missing { .. })));
+        assert!(matches!(store.load_history(), Err(Error::Missing { .. })));
+        assert!(store.list().unwrap().is_empty());
+    }
+
+    #[test]
+    fn save_and_load() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = FileStore::new(dir.path().to_path_buf());
+
+        store.save(b"one", b"started\n").unwrap();
+        store.save(b"two", b"next\n").unwrap();
+
+        assert_eq!(store.load().unwrap(), b"two");
+        assert_eq!(store.load_history().unwrap(), b"started\nnext\n");
+    }
+
+    #[test]
+    fn conflict() {
+        let dir = tempfile::tempdir().unwrap();
+        let alice = FileStore::new(dir.path().to_path_buf());
+        let bob = FileStore::new(dir.path().to_path_buf());
+
+        alice.save(b"one", b"").unwrap();
+        bob.load().unwrap();
+        alice.load().unwrap();
+        alice.save(b"alice", b"").unwrap();
+
+        assert!(matches!(bob.save(b"bob", b""), Err(Error::Conflict)));
+        bob.load().unwrap();
+        bob.save(b"bob", b"").unwrap();
+        assert_eq!(alice.load().unwrap(), b"bob");
+    }
+
+    #[test]
+    fn named_sessions() {
+        let dir = tempfile::tempdir().unwrap();
+        let default = FileStore::new(dir.path().to_path_buf());
+        let search = FileStore::new(dir.path().to_path_buf()).with_session(Some("search".into()));
+
+        default.save(b"default", b"").unwrap();
+        search.save(b"search", b"").unwrap();
+        assert_eq!(
+            default.list().unwrap(),
+            vec![
+                ("default".to_string(), b"default".to_vec()),
+                ("search".to_string(), b"search".to_vec()),
+            ]
+        );
+
+        search.clean().unwrap();
+        assert_eq!(default.list().unwrap().len(), 1);
+    }
+}
@@ -1,7 +1,16 @@
+mod bundle;
+mod dry_run;
+mod file_store;
+mod saved_work;
 pub mod store;
 use crate::command;
 use crate::os;
 use anyhow::{Result, anyhow};
+pub use bundle::Bundle;
+use chrono::Utc;
+pub use dry_run::DryRun;
+pub use file_store::FileStore;
+pub use saved_work::{SavedKind, SavedWork};
 use std::env;
 use std::path::PathBuf;
 pub use store::Store;
@@ -12,12 +21,40 @@ pub trait Git {
     fn has_branch(&self, branch: &str) -> Result<bool>;
     fn current_branch(&self) -> Result<Option<String>>;
     fn dirty_files(&self) -> Result<String>;
+    fn commit_id(&self, rev: &str) -> Result<Option<String>>;
+    fn changed_files(&self, from: &str, to: &str) -> Result<Vec<String>>;
+    /// Number of commits in `from..to`
+    fn count_commits(&self, from: &str, to: &str) -> Result<usize>;
+    /// Name and email of the author of each commit in `from..to`
+    fn authors(&self, from: &str, to: &str) -> Result<Vec<(String, String)>>;
+    /// Path of a file in the git directory, e.g. SQUASH_MSG
+    fn git_path(&self, name: &str) -> Result<PathBuf>;
+    /// Saves `rev` below refs/mob/backup, returns the backup ref or None
+    /// if `rev` doesn't exist
+    fn backup(&self, rev: &str) -> Result<Option<String>>;
+    /// Saves HEAD with the uncommitted changes and untracked files of the
+    /// working tree below refs/mob/backup
+    fn backup_worktree(&self) -> Result<Option<String>>;
+    /// Stashes and backups made by mob, newest first
+    fn saved_work(&self) -> Result<Vec<SavedWork>>;
+    /// Summary of the changes in `range`, like "1 file changed, 2 insertions(+)"
+    fn diffstat(&self, range: &str) -> Result<String>;
 }
 
+/// Where backups are saved before mob deletes anything
+pub const BACKUP_REFS: &str = "refs/mob/backup";
+/// Message of the stashes made by mob start
+pub const STASH_MESSAGE: &str = "set aside by mob start";
+
 #[derive(Debug)]
 pub struct CommitFile<'a> {
     pub filename: &'a str,
     pub data: &'a [u8],
+}
+
+#[derive(Debug)]
+pub struct Commit<'a> {
+    pub files: &'a [CommitFile<'a>],
     pub message: &'a str,
     pub reference: &'a str,
 }
@@ -25,6 +62,9 @@ pub struct CommitFile<'a> {
 pub struct GitCommand {
     command: command::Command<'static>,
     pub remote: String,
+    pub session: String,
+    /// Sessions are read without updating any local ref
+    read_only: bool,
 }
 
 impl GitCommand {
@@ -37,7 +77,47 @@ impl GitCommand {
             .run_stdout(["rev-parse", "--git-dir"])
             .map_err(|_| anyhow!("Not a git repository: {}", path.display()))?;
 
-        Ok(Self { command, remote })
+        Ok(Self {
+            command,
+            remote,
+            session: store::DEFAULT_SESSION.to_string(),
+            read_only: false,
+        })
+    }
+
+    /// Use a named session instead of the default one. Its branches are
+    /// named after it, so the name must make a valid branch that no other
+    /// session's metadata can end up on.
+    pub fn with_session(self, session: Option<String>) -> Result<Self> {
+        let Some(session) = session else {
+            return Ok(self);
+        };
+        if session.starts_with("meta") {
+            return Err(anyhow!(
+                "Session names can't start with meta, mob keeps its data in mob-meta branches"
+            ));
+        }
+        self.command
+            .run_checked([
+                "check-ref-format",
+                "--branch",
+                &store::meta_branch(&session),
+            ])
+            .map_err(|_| anyhow!("{} can't be used in a branch name", session))?;
+        Ok(Self { session, ..self })
+    }
+
+    /// Read sessions without touching the local session branches or
+    /// imported refs, for dry runs that never save
+    pub fn read_only(self) -> Self {
+        Self {
+            read_only: true,
+            ..self
+        }
+    }
+
+    fn meta_branch(&self) -> String {
+        store::meta_branch(&self.session)
     }
 
     fn last_commit_oid(&self, reference: &str) -> Option<String> {
@@ -49,19 +129,24 @@ impl GitCommand {
             .map(|s| s.trim().to_string())
     }
 
-    pub fn create_commit(&self, commit: &CommitFile) -> Result<String> {
-        // 1. Create blob from data using git hash-object
-        let blob_oid = self
-            .command
-            .run_with_stdin(["hash-object", "-w", "--stdin"], commit.data)?;
-        let blob_oid = blob_oid.trim();
-
+    pub fn create_commit(&self, commit: &Commit) -> Result<String> {
+        // 1. Create blobs from data using git hash-object
         // 2. Create tree using git mktree
         // Format: <mode> <type> <hash>\t<filename>
-        let tree_entry = format!("100644 blob {}\t{}\n", blob_oid, commit.filename);
+        let mut tree_entries = String::new();
+        for file in commit.files {
+            let blob_oid = self
+                .command
+                .run_with_stdin(["hash-object", "-w", "--stdin"], file.data)?;
+            tree_entries.push_str(&format!(
+                "100644 blob {}\t{}\n",
+                blob_oid.trim(),
+                file.filename
+            ));
+        }
         let tree_oid = self
             .command
-            .run_with_stdin(["mktree"], tree_entry.as_bytes())?;
+            .run_with_stdin(["mktree"], tree_entries.as_bytes())?;
         let tree_oid = tree_oid.trim();
 
         // 3. Create commit using git commit-tree
@@ -108,6 +193,23 @@ impl GitCommand {
         Ok(output.stdout.into_bytes())
     }
 
+    /// Backups within the same second are numbered, an existing backup is
+    /// never overwritten
+    fn save_backup(&self, name: &str, commit: &str) -> Result<String> {
+        let time = Utc::now().format("%Y%m%d-%H%M%S").to_string();
+        let mut reference = format!("{}/{}/{}", BACKUP_REFS, time, name);
+        let mut number = 1;
+        while self.commit_id(&reference)?.is_some() {
+            number += 1;
+            reference = format!("{}/{}.{}/{}", BACKUP_REFS, time, number, name);
+        }
+        // The empty old value makes git refuse to replace a ref made meanwhile
+        self.command
+            .run_checked(["update-ref", &reference, commit, ""])?;
+        log::info!("Saved {} as {}", name, reference);
+        Ok(reference)
+    }
+
     fn run_quietly(&self, args: &[&str]) -> Result<()> {
         log::trace!("running git {}", args.join(" "));
         self.command.run_checked(args)
@@ -149,4 +251,102 @@ impl Git for GitCommand {
     fn dirty_files(&self) -> Result<String> {
         self.command.run_stdout(["status", "--short"])
     }
+
+    fn commit_id(&self, rev: &str) -> Result<Option<String>> {
+        let rev = format!("{}^{{commit}}", rev);
+        let output = self
+            .command
+            .run(["rev-parse", "--verify", "--quiet", &rev])?;
+
+        if !output.status.success() {
+            return Ok(None);
+        }
+        Ok(Some(output.stdout.trim().to_string()))
+    }
+
+    fn changed_files(&self, from: &str, to: &str) -> Result<Vec<String>> {
+        let range = format!("{}..{}", from, to);
+        let output = self.command.run_stdout(["diff", "--name-only", &range])?;
+        Ok(output.lines().map(|line| line.to_string()).collect())
+    }
+
+    fn count_commits(&self, from: &str, to: &str) -> Result<usize> {
+        let range = format!("{}..{}", from, to);
+        let output = self.command.run_stdout(["rev-list", "--count", &range])?;
+        Ok(output.trim().parse()?)
+    }
+
+    fn authors(&self, from: &str, to: &str) -> Result<Vec<(String, String)>> {
+        let range = format!("{}..{}", from, to);
+        let output =
+            self.command
+                .run_stdout(["log", "--reverse", "--format=%aN%x00%aE", &range])?;
+        Ok(output
+            .lines()
+            .filter_map(|line| line.split_once('\0'))
+            .map(|(name, email)| (name.to_string(), email.to_string()))
+            .collect())
+    }
+
+    fn git_path(&self, name: &str) -> Result<PathBuf> {
+        let path =
+            self.command
+                .run_stdout(["rev-parse", "--path-format=absolute", "--git-path", name])?;
+        Ok(PathBuf::from(path.trim()))
+    }
+
+    fn backup(&self, rev: &str) -> Result<Option<String>> {
+        match self.commit_id(rev)? {
+            Some(commit) => self.save_backup(rev, &commit).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    fn backup_worktree(&self) -> Result<Option<String>> {
+        let Some(head) = self.commit_id("HEAD")? else {
+            return Ok(None);
+        };
+
+        // Stage everything in a temporary index to leave the real one alone
+        let dir = tempfile::tempdir()?;
+        let index = self
+            .command
+            .clone()
+            .env("GIT_INDEX_FILE", dir.path().join("index"));
+        index.run_checked(["read-tree", &head])?;
+        index.run_checked(["add", "--all"])?;
+        let tree = index.run_stdout(["write-tree"])?;
+
+        // Named like stashes so rescue can tell where it came from
+        let branch = self
+            .current_branch()?
+            .unwrap_or_else(|| "(no branch)".to_string());
+        let message = format!("mob backup of the working tree on {}", branch);
+        let commit = self.command.run_with_stdin(
+            ["commit-tree", tree.trim(), "-p", &head, "-m", &message],
+            &[],
+        )?;
+        self.save_backup("HEAD", commit.trim()).map(Some)
+    }
+
+    fn saved_work(&self) -> Result<Vec<SavedWork>> {
+        let stashes = self
+            .command
+            .run_stdout(["stash", "list", "--format=%gd%x00%ct%x00%gs"])?;
+        let backups = self.command.run_stdout([
+            "for-each-ref",
+            "--format=%(refname)%00%(subject)",
+            BACKUP_REFS,
+        ])?;
+
+        let mut saved = saved_work::parse_stashes(&stashes);
+        saved.extend(saved_work::parse_backups(&backups));
+        saved.sort_by_key(|work| std::cmp::Reverse(work.time));
+        Ok(saved)
+    }
+
+    fn diffstat(&self, range: &str) -> Result<String> {
+        let output = self.command.run_stdout(["diff", "--shortstat", range])?;
+        Ok(output.trim().to_string())
+    }
 }
@@ -0,0 +1,148 @@
+use super::{BACKUP_REFS, STASH_MESSAGE};
+use chrono::{DateTime, NaiveDateTime, TimeZone, Utc};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SavedKind {
+    /// Changes stashed by mob start
+    Stash,
+    /// Discarded changes, a commit on top of what was checked out
+    Worktree,
+    /// A branch mob deleted or reset
+    Branch,
+}
+
+impl SavedKind {
+    pub fn description(&self) -> &'static str {
+        match self {
+            SavedKind::Stash => "stash",
+            SavedKind::Worktree => "discarded",
+            SavedKind::Branch => "branch",
+        }
+    }
+}
+
+/// Work mob set aside, a stash or a backup ref
+#[derive(Debug, Clone, PartialEq)]
+pub struct SavedWork {
+    pub kind: SavedKind,
+    /// Stash entry or backup ref to restore from
+    pub reference: String,
+    pub time: DateTime<Utc>,
+    pub branch: Option<String>,
+}
+
+impl SavedWork {
+    /// The range with the saved changes, for a diffstat
+    pub fn range(&self) -> String {
+        match self.kind {
+            SavedKind::Stash | SavedKind::Worktree => {
+                format!("{}^1..{}", self.reference, self.reference)
+            }
+            SavedKind::Branch => format!("HEAD...{}", self.reference),
+        }
+    }
+}
+
+/// Parses `git stash list --format=%gd%x00%ct%x00%gs`, keeping the stashes
+/// made by mob
+pub(super) fn parse_stashes(output: &str) -> Vec<SavedWork> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split('\0');
+            let (reference, time, subject) = (fields.next()?, fields.next()?, fields.next()?);
+            // Subjects look like "On main: <message>"
+            let (on, message) = subject.split_once(": ")?;
+            if message != STASH_MESSAGE {
+                return None;
+            }
+            Some(SavedWork {
+                kind: SavedKind::Stash,
+                reference: reference.to_string(),
+                time: Utc.timestamp_opt(time.parse().ok()?, 0).single()?,
+                branch: on.strip_prefix("On ").map(|branch| branch.to_string()),
+            })
+        })
+        .collect()
+}
+
+/// Parses `git for-each-ref --format=%(refname)%00%(subject)` of the backup
+/// refs, named <time>[.<number>]/<what was backed up>
+pub(super) fn parse_backups(output: &str) -> Vec<SavedWork> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let (reference, subject) = line.split_once('\0')?;
+            let name = reference.strip_prefix(BACKUP_REFS)?.strip_prefix('/')?;
+            let (time, rev) = name.split_once('/')?;
+            let time = time.split_once('.').map_or(time, |(time, _)| time);
+            let time = NaiveDateTime::parse_from_str(time, "%Y%m%d-%H%M%S").ok()?;
+
+            let (kind, branch) = match rev {
+                "HEAD" => (
+                    SavedKind::Worktree,
+                    subject
+                        .rsplit_once(" on ")
+                        .map(|(_, branch)| branch.to_string()),
+                ),
+                branch => (SavedKind::Branch, Some(branch.to_string())),
+            };
+            Some(SavedWork {
+                kind,
+                reference: reference.to_string(),
+                time: Utc.from_utc_datetime(&time),
+                branch,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_mob_stashes() {
+        let output = format!(
+            "stash@{{0}}\x001634000000\x00WIP on main: abc123 init\n\
+             stash@{{1}}\x001633000000\x00On mob-session: {}\n",
+            STASH_MESSAGE
+        );
+
+        let stashes = parse_stashes(&output);
+        assert_eq!(
+            stashes,
+            vec![SavedWork {
+                kind: SavedKind::Stash,
+                reference: "stash@{1}".to_string(),
+                time: Utc.timestamp_opt(1633000000, 0).unwrap(),
+                branch: Some("mob-session".to_string()),
+            }]
+        );
+        assert_eq!(stashes[0].range(), "stash@{1}^1..stash@{1}");
+    }
+
+    #[test]
+    fn backups() {
+        let output = "refs/mob/backup/20211010-142241/HEAD\x00mob backup of the working tree on main\n\
+             refs/mob/backup/20211010-142241/origin/mob-session\x00mob sync\n\
+             refs/mob/backup/20211010-142241.2/HEAD\x00mob backup of the working tree on main\n\
+             refs/mob/backup/not-a-time/HEAD\x00mob backup of the working tree on main\n";
+
+        let backups = parse_backups(output);
+        assert_eq!(backups.len(), 3);
+        assert_eq!(backups[0].kind, SavedKind::Worktree);
+        assert_eq!(backups[0].branch, Some("main".to_string()));
+        assert_eq!(
+            backups[0].time,
+            Utc.with_ymd_and_hms(2021, 10, 10, 14, 22, 41).unwrap()
+        );
+        assert_eq!(backups[1].kind, SavedKind::Branch);
+        assert_eq!(backups[1].branch, Some("origin/mob-session".to_string()));
+        assert_eq!(
+            backups[1].range(),
+            "HEAD...refs/mob/backup/20211010-142241/origin/mob-session"
+        );
+        assert_eq!(backups[2].time, backups[0].time);
+    }
+}
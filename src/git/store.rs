@@ -1,9 +1,11 @@
 use anyhow::anyhow;
 
-use super::{CommitFile, GitCommand, Result, store};
+use super::{Commit, CommitFile, GitCommand, Result, store};
 
-const SESSION_FILENAME: &str = "data";
+pub(super) const SESSION_FILENAME: &str = "data";
+pub(super) const HISTORY_FILENAME: &str = "history";
 const SESSION_HEAD: &str = "mob-meta";
+pub const DEFAULT_SESSION: &str = "default";
 const COMMIT_MESSAGE: &str = "mob metadata changed [skip ci]";
 
 #[derive(thiserror::Error, Debug)]
@@ -11,73 +13,218 @@ pub enum Error {
     #[error("unknown error")]
     Unknown(#[from] anyhow::Error),
 
-    #[error("failed to push to origin")]
+    #[error("push rejected, someone else changed the session")]
     Conflict,
 
     #[error("missing config")]
     Missing { source: anyhow::Error },
 }
 
+/// Branch holding the metadata of a session
+pub fn meta_branch(session: &str) -> String {
+    if session == DEFAULT_SESSION {
+        SESSION_HEAD.to_string()
+    } else {
+        format!("{}-{}", SESSION_HEAD, session)
+    }
+}
+
+/// The session whose metadata `meta_branch` holds, if it's a meta branch
+pub fn session_name(meta_branch: &str) -> Option<String> {
+    if meta_branch == SESSION_HEAD {
+        return Some(DEFAULT_SESSION.to_string());
+    }
+    meta_branch
+        .strip_prefix(SESSION_HEAD)?
+        .strip_prefix('-')
+        .map(|name| name.to_string())
+}
+
 pub trait Store {
+    /// Name of the session that is loaded and saved
+    fn session(&self) -> &str;
     fn load(&self) -> Result<Vec<u8>, Error>;
-    fn save(&self, data: &[u8]) -> Result<(), Error>;
+    /// Save session data and append `history` to the history
+    fn save(&self, data: &[u8], history: &[u8]) -> Result<(), Error>;
+    fn load_history(&self) -> Result<Vec<u8>, Error>;
+    /// Session data of all sessions by name
+    fn list(&self) -> Result<Vec<(String, Vec<u8>)>, Error>;
     fn clean(&self) -> Result<(), Error>;
 }
 
-impl Store for GitCommand {
-    fn save(&self, data: &[u8]) -> Result<(), store::Error> {
-        let filename = SESSION_FILENAME;
-
-        let commit = CommitFile {
-            filename,
-            data,
-            reference: SESSION_HEAD,
-            message: COMMIT_MESSAGE,
-        };
-
-        self.create_commit(&commit)?;
-
-        self.run_quietly(&[
-            "push",
-            "--no-verify",
-            self.remote.as_str(),
-            format!("{}:{}", SESSION_HEAD, SESSION_HEAD).as_str(),
-        ])
-        .map_err(|_| store::Error::Conflict) // TODO: should check for "rejected" in output
-    }
+impl GitCommand {
+    /// Fetches the session branch, returns the commit to read it from
+    fn fetch_meta(&self, meta_branch: &str) -> Result<String, store::Error> {
+        if self.read_only {
+            // Only FETCH_HEAD is written, which the next fetch replaces
+            self.run_quietly(&["fetch", "--quiet", self.remote.as_str(), meta_branch])
+                .map_err(|err| Error::Missing {
+                    source: err.context("Could not fetch repo"),
+                })?;
+            return Ok(self
+                .command
+                .run_stdout(["rev-parse", "FETCH_HEAD"])?
+                .trim()
+                .to_string());
+        }
 
-    fn load(&self) -> Result<Vec<u8>, store::Error> {
-        if let Err(e) = self.run_quietly(&["branch", "-D", SESSION_HEAD]) {
+        if let Err(e) = self.run_quietly(&["branch", "-D", meta_branch]) {
             log::trace!("Failed to delete or missing local branch: {}", e);
         }
 
         self.run_quietly(&[
             "fetch",
             self.remote.as_str(),
-            format!("{}:{}", SESSION_HEAD, SESSION_HEAD).as_str(),
+            format!("{}:{}", meta_branch, meta_branch).as_str(),
         ])
         .map_err(|err| Error::Missing {
             source: err.context("Could not fetch repo"),
         })?;
+        Ok(meta_branch.to_string())
+    }
+
+    fn load_meta(&self, meta_branch: &str) -> Result<Vec<u8>, store::Error> {
+        let reference = self.fetch_meta(meta_branch)?;
 
         // Use git show to read the file content from the branch
-        self.show_file(SESSION_HEAD, SESSION_FILENAME)
+        self.show_file(&reference, SESSION_FILENAME)
             .map_err(|err| Error::Missing {
                 source: anyhow!("Could not read session data: {}", err),
             })
     }
 
+    pub(super) fn local_history(&self) -> Vec<u8> {
+        self.history_at(&self.meta_branch())
+    }
+
+    pub(super) fn history_at(&self, reference: &str) -> Vec<u8> {
+        // Sessions from older versions have no history
+        self.show_file(reference, HISTORY_FILENAME)
+            .unwrap_or_default()
+    }
+
+    /// Commits session data and appends `history` on the local session
+    /// branch, returns the commit
+    pub(super) fn commit_meta(&self, data: &[u8], history: &[u8]) -> Result<String> {
+        let mut full_history = self.local_history();
+        full_history.extend_from_slice(history);
+
+        let files = [
+            CommitFile {
+                filename: SESSION_FILENAME,
+                data,
+            },
+            CommitFile {
+                filename: HISTORY_FILENAME,
+                data: &full_history,
+            },
+        ];
+        let meta_branch = self.meta_branch();
+        let commit = Commit {
+            files: &files,
+            reference: &meta_branch,
+            message: COMMIT_MESSAGE,
+        };
+
+        self.create_commit(&commit)
+    }
+}
+
+impl Store for GitCommand {
+    fn session(&self) -> &str {
+        &self.session
+    }
+
+    fn save(&self, data: &[u8], history: &[u8]) -> Result<(), store::Error> {
+        self.commit_meta(data, history)?;
+        let meta_branch = self.meta_branch();
+
+        // Without force the push is rejected if someone pushed since we fetched
+        let refspec = format!("{}:{}", meta_branch, meta_branch);
+        let output = self
+            .command
+            .run([
+                "push",
+                "--no-verify",
+                self.remote.as_str(),
+                refspec.as_str(),
+            ])
+            .map_err(anyhow::Error::from)?;
+        if output.status.success() {
+            return Ok(());
+        }
+        if output.stderr.contains("[rejected]") {
+            return Err(store::Error::Conflict);
+        }
+        Err(store::Error::Unknown(output.into_error().into()))
+    }
+
+    fn load(&self) -> Result<Vec<u8>, store::Error> {
+        self.load_meta(&self.meta_branch())
+    }
+
+    fn load_history(&self) -> Result<Vec<u8>, store::Error> {
+        let reference = self.fetch_meta(&self.meta_branch())?;
+        Ok(self.history_at(&reference))
+    }
+
+    fn list(&self) -> Result<Vec<(String, Vec<u8>)>, store::Error> {
+        let pattern = format!("refs/heads/{}*", SESSION_HEAD);
+        let output =
+            self.command
+                .run_stdout(["ls-remote", "--heads", self.remote.as_str(), &pattern])?;
+
+        let mut sessions = Vec::new();
+        for line in output.lines() {
+            let meta_branch = match line.split_once("refs/heads/") {
+                Some((_, meta_branch)) => meta_branch,
+                None => continue,
+            };
+            if let Some(name) = session_name(meta_branch) {
+                sessions.push((name, self.load_meta(meta_branch)?));
+            }
+        }
+        Ok(sessions)
+    }
+
     fn clean(&self) -> Result<(), store::Error> {
-        self.run_quietly(&["branch", "-D", SESSION_HEAD])
+        let meta_branch = self.meta_branch();
+        self.run_quietly(&["branch", "-D", &meta_branch])
             .unwrap_or_else(|err| log::trace!("Failed to delete local branch: {}", err));
         self.run_quietly(&[
             "push",
             self.remote.as_str(),
             "--no-verify",
             "--delete",
-            SESSION_HEAD,
+            &meta_branch,
         ])
         .unwrap_or_else(|err| log::trace!("Failed to remove remote branch: {}", err));
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_session() {
+        assert_eq!(meta_branch(DEFAULT_SESSION), "mob-meta");
+        assert_eq!(session_name("mob-meta"), Some(DEFAULT_SESSION.to_string()));
+    }
+
+    #[test]
+    fn named_session() {
+        assert_eq!(meta_branch("feature"), "mob-meta-feature");
+        assert_eq!(
+            session_name("mob-meta-feature"),
+            Some("feature".to_string())
+        );
+    }
+
+    #[test]
+    fn not_a_session() {
+        assert_eq!(session_name("mob-metadata"), None);
+        assert_eq!(session_name("main"), None);
+    }
+}
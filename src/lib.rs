@@ -1,5 +1,7 @@
 pub mod cmd;
+#[allow(clippy::useless_borrows_in_formatting)]
 mod command;
+#[allow(clippy::useless_borrows_in_formatting)]
 pub mod config;
 pub mod duration;
 pub mod emoji_logger;
@@ -1,12 +1,18 @@
 use anyhow::Result;
 use clap::Parser;
-use remotemob::{
-    cmd, config, emoji_logger, git, prompt::DialoguerPrompter, session, session::Store,
-};
+use remotemob::{cmd, config, emoji_logger, git, prompt::DialoguerPrompter, session};
 
 #[derive(Parser)]
 #[clap(version = clap::crate_version!(), author = clap::crate_authors!())]
 struct Opts {
+    /// Name of the session, to run several mobs in one repository
+    #[clap(long, global = true, env = "MOB_SESSION")]
+    session: Option<String>,
+
+    /// Show what would be done without changing git or the session
+    #[clap(long, global = true)]
+    dry_run: bool,
+
     #[clap(subcommand)]
     subcmd: SubCommand,
 }
@@ -17,14 +23,50 @@ enum SubCommand {
     #[clap(name = "status")]
     Status(cmd::StatusOpts),
 
+    /// Show history of turns
+    #[clap(name = "log")]
+    Log(cmd::LogOpts),
+
+    /// Show and change session settings
+    #[clap(name = "settings")]
+    Settings(cmd::SettingsOpts),
+
     /// Reorder drivers
     #[clap(name = "order")]
     Order,
 
-    /// Clean up all mob related stuff from this repo
+    /// Join the rotation without starting a turn
+    #[clap(name = "join")]
+    Join(cmd::JoinOpts),
+
+    /// Leave the rotation
+    #[clap(name = "leave")]
+    Leave,
+
+    /// Mark yourself or NAME as away, the rotation skips away drivers
+    #[clap(name = "away")]
+    Away(cmd::PresenceOpts),
+
+    /// Mark yourself or NAME as back in the rotation
+    #[clap(name = "back")]
+    Back(cmd::PresenceOpts),
+
+    /// Pause the mob, start and next are refused until resumed
+    #[clap(name = "pause")]
+    Pause(cmd::PauseOpts),
+
+    /// Resume a paused mob
+    #[clap(name = "resume")]
+    Resume,
+
+    /// Clean up all mob related stuff of the session from this repo
     #[clap(name = "clean")]
     Clean,
 
+    /// List and restore work mob stashed or backed up
+    #[clap(name = "rescue")]
+    Rescue,
+
     /// Start mob session
     #[clap(name = "start")]
     Start(cmd::StartOpts),
@@ -33,9 +75,21 @@ enum SubCommand {
     #[clap(name = "next")]
     Next,
 
-    /// Stop session and stage all changes to commit
+    /// Take back your handover before the next driver starts
+    #[clap(name = "undo")]
+    Undo,
+
+    /// Pass your turn to the next driver without driving
+    #[clap(name = "skip")]
+    Skip,
+
+    /// Bring changes on the base branch into the mob branch
+    #[clap(name = "sync-base")]
+    SyncBase(cmd::SyncBaseOpts),
+
+    /// Stop session and bring the changes into the base branch
     #[clap(name = "done")]
-    Done,
+    Done(cmd::DoneOpts),
 }
 
 fn main() {
@@ -55,17 +109,67 @@ fn run() -> Result<()> {
 
     let config = config::load()?;
 
-    let git = git::GitCommand::new(None, config.remote.clone())?;
-    let store = session::SessionStore::new(&git);
+    let git =
+        git::GitCommand::new(None, config.remote.clone())?.with_session(opts.session.clone())?;
+    // Not even the local session branch is updated in a dry run
+    let git = if opts.dry_run { git.read_only() } else { git };
+    match config.bundle_dir.clone() {
+        Some(dir) => run_with(opts, &git::Bundle::new(git, dir), config),
+        None => run_with(opts, &git, config),
+    }
+}
+
+fn run_with(opts: Opts, git: &(impl git::Git + git::Store), config: config::Config) -> Result<()> {
+    let file_store = config
+        .meta_dir
+        .clone()
+        .map(|dir| git::FileStore::new(dir).with_session(opts.session));
+    let store = match &file_store {
+        Some(file_store) => session::SessionStore::new(file_store),
+        None => session::SessionStore::new(git),
+    };
+
+    if opts.dry_run {
+        log::info!("Dry run, nothing is changed and hooks are not run");
+        let config = config::Config {
+            dry_run: true,
+            hooks: config::Hooks::new(None),
+            ..config
+        };
+        let git = git::DryRun::new(git);
+        let store = session::DryRun::new(&store);
+        return run_subcommand(opts.subcmd, &git, &store, config);
+    }
+    run_subcommand(opts.subcmd, git, &store, config)
+}
+
+fn run_subcommand(
+    subcmd: SubCommand,
+    git: &impl git::Git,
+    store: &impl session::Store,
+    config: config::Config,
+) -> Result<()> {
     let prompter = DialoguerPrompter;
 
-    match opts.subcmd {
-        SubCommand::Start(opts) => cmd::Start::new(&git, &store, &prompter, opts, config).run()?,
-        SubCommand::Next => cmd::Next::new(&git, &store, config).run()?,
-        SubCommand::Done => cmd::Done::new(&git, &store, &prompter, config).run()?,
+    match subcmd {
+        SubCommand::Start(opts) => cmd::Start::new(git, store, &prompter, opts, config).run()?,
+        SubCommand::Next => cmd::Next::new(git, store, &prompter, config).run()?,
+        SubCommand::Undo => cmd::Undo::new(git, store, config).run()?,
+        SubCommand::Skip => cmd::Skip::new(store, config).run()?,
+        SubCommand::Done(opts) => cmd::Done::new(git, store, &prompter, opts, config).run()?,
         SubCommand::Clean => store.clean()?,
-        SubCommand::Status(opts) => cmd::Status::new(opts, &store, config).run()?,
-        SubCommand::Order => cmd::Order::new(&store, &prompter).run()?,
+        SubCommand::Rescue => cmd::Rescue::new(git, &prompter).run()?,
+        SubCommand::SyncBase(opts) => cmd::SyncBase::new(opts, git, store, config).run()?,
+        SubCommand::Status(opts) => cmd::Status::new(opts, git, store, config).run()?,
+        SubCommand::Settings(opts) => cmd::Settings::new(opts, store, &prompter).run()?,
+        SubCommand::Order => cmd::Order::new(store, &prompter).run()?,
+        SubCommand::Join(opts) => cmd::Join::new(opts, store, &prompter, config).run()?,
+        SubCommand::Leave => cmd::Leave::new(store, config).run()?,
+        SubCommand::Away(opts) => cmd::Presence::away(opts, store, config).run()?,
+        SubCommand::Back(opts) => cmd::Presence::back(opts, store, config).run()?,
+        SubCommand::Pause(opts) => cmd::Pause::new(opts, store, config).run()?,
+        SubCommand::Resume => cmd::Resume::new(store, config).run()?,
+        SubCommand::Log(opts) => cmd::Log::new(opts, git, store).run()?,
     };
     Ok(())
 }
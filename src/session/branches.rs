@@ -1,3 +1,4 @@
+use crate::git::store::DEFAULT_SESSION;
 use crate::prompt::Prompter;
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
@@ -15,6 +16,17 @@ impl Branches {
             base_branch: format!("{}/{}", remote, self.base_branch),
         }
     }
+    /// Default branches of the session `name`
+    pub fn for_session(name: &str) -> Self {
+        if name == DEFAULT_SESSION {
+            return Branches::default();
+        }
+        Self {
+            branch: format!("mob-{}", name),
+            ..Branches::default()
+        }
+    }
+
     pub fn ask(prompter: &dyn Prompter, default: Branches) -> Result<Branches> {
         if default.base_branch != "master" && default.base_branch != "main" {
             log::info!("Note that you are not on main or master");
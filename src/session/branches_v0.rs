@@ -0,0 +1,17 @@
+use super::branches::Branches;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BranchesV0 {
+    pub branch: String,
+    pub base_branch: String,
+}
+
+impl From<BranchesV0> for Branches {
+    fn from(branches_v0: BranchesV0) -> Self {
+        Branches {
+            branch: branches_v0.branch,
+            base_branch: branches_v0.base_branch,
+        }
+    }
+}
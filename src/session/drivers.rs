@@ -1,15 +1,72 @@
 use serde::{Deserialize, Serialize};
 
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Driver {
+    /// Display name
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub email: Option<String>,
+    /// Stable id, preferred over email to identify the driver
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    /// Away drivers keep their place but are skipped in the rotation
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub away: bool,
+}
+
+impl Driver {
+    pub fn new(name: &str, email: Option<String>, id: Option<String>) -> Self {
+        Driver {
+            name: name.to_string(),
+            email,
+            id,
+            away: false,
+        }
+    }
+
+    fn named(name: &str) -> Self {
+        Driver::new(name, None, None)
+    }
+
+    /// Identifies the driver in the state and history
+    pub fn key(&self) -> &str {
+        self.id
+            .as_deref()
+            .or(self.email.as_deref())
+            .unwrap_or(&self.name)
+    }
+
+    /// Same id, or same email, or same name if either lacks both
+    pub fn same_person(&self, other: &Driver) -> bool {
+        match (&self.id, &other.id, &self.email, &other.email) {
+            (Some(id), Some(other_id), _, _) => id == other_id,
+            (_, _, Some(email), Some(other_email)) => email.eq_ignore_ascii_case(other_email),
+            _ => self.name == other.name,
+        }
+    }
+
+    /// True if `key` is the key, id, email or name of the driver
+    fn known_as(&self, key: &str) -> bool {
+        self.key() == key
+            || self.id.as_deref() == Some(key)
+            || self
+                .email
+                .as_deref()
+                .is_some_and(|email| email.eq_ignore_ascii_case(key))
+            || self.name == key
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
-pub struct Drivers(Vec<String>);
+pub struct Drivers(Vec<Driver>);
 
 impl Drivers {
     pub fn new(drivers: Vec<String>) -> Self {
-        Drivers(drivers)
+        Drivers(drivers.iter().map(|name| Driver::named(name)).collect())
     }
 
-    pub fn insert(mut self, after: Option<String>, name: &str) -> Self {
-        if self.contains(name) {
+    pub fn insert(mut self, after: Option<String>, driver: Driver) -> Self {
+        if self.find(&driver).is_some() {
             return self;
         }
 
@@ -21,36 +78,80 @@ impl Drivers {
         match index {
             Some(i) => {
                 if i + 1 > self.0.len() {
-                    self.0.push(name.to_string());
+                    self.0.push(driver);
                 } else {
-                    self.0.insert(i + 1, name.to_string());
+                    self.0.insert(i + 1, driver);
                 }
             }
-            None => self.0.push(name.to_string()),
+            None => self.0.push(driver),
         }
         self
     }
 
+    /// The driver that is the same person as `driver`
+    pub fn find(&self, driver: &Driver) -> Option<&Driver> {
+        self.0.iter().find(|d| d.same_person(driver))
+    }
+
+    /// Updates the identity of the same person as `driver`, returns the
+    /// previous key if it changed
+    pub fn identify(&mut self, driver: &Driver) -> Option<String> {
+        let existing = self.0.iter_mut().find(|d| d.same_person(driver))?;
+        let previous = existing.key().to_string();
+        existing.name = driver.name.clone();
+        existing.email = driver.email.clone().or(existing.email.take());
+        existing.id = driver.id.clone().or(existing.id.take());
+        (existing.key() != previous).then_some(previous)
+    }
+
+    /// Display name of the driver with `key`, with the email if the name
+    /// is shared by several drivers
+    pub fn name(&self, key: &str) -> String {
+        let Some(driver) = self.get(key) else {
+            return key.to_string();
+        };
+        let shared = self.0.iter().filter(|d| d.name == driver.name).count() > 1;
+        match &driver.email {
+            Some(email) if shared => format!("{} <{}>", driver.name, email),
+            _ => driver.name.clone(),
+        }
+    }
+
+    pub fn get(&self, key: &str) -> Option<&Driver> {
+        self.position(key).map(|i| &self.0[i])
+    }
+
     pub fn contains(&self, name: &str) -> bool {
-        self.0.contains(&name.to_string())
+        self.position(name).is_some()
+    }
+
+    fn position(&self, key: &str) -> Option<usize> {
+        self.0
+            .iter()
+            .position(|d| d.key() == key)
+            .or_else(|| self.0.iter().position(|d| d.known_as(key)))
     }
 
-    fn position(&self, driver: &str) -> Option<usize> {
-        self.0.iter().position(|name| name == driver)
+    /// First present driver when walking from `current` in steps of `step`
+    fn walk(&self, current: &str, step: impl Fn(usize) -> usize) -> Option<String> {
+        let mut index = self.position(current)?;
+        for _ in 0..self.0.len() {
+            index = step(index);
+            if !self.0[index].away {
+                return Some(self.0[index].key().to_string());
+            }
+        }
+        None
     }
 
     pub fn next(&self, current: &str) -> Option<String> {
-        self.position(current).map(|i| {
-            let next_index = (i + 1) % self.0.len();
-            self.0[next_index].clone()
-        })
+        let len = self.0.len();
+        self.walk(current, |i| (i + 1) % len)
     }
 
     pub fn prev(&self, current: &str) -> Option<String> {
-        self.position(current).map(|i| {
-            let prev_index = if i == 0 { self.0.len() - 1 } else { i - 1 };
-            self.0[prev_index].clone()
-        })
+        let len = self.0.len();
+        self.walk(current, |i| if i == 0 { len - 1 } else { i - 1 })
     }
 
     pub fn remove(mut self, name: &str) -> Self {
@@ -61,8 +162,64 @@ impl Drivers {
         self
     }
 
+    pub fn set_away(mut self, name: &str, away: bool) -> Self {
+        if let Some(index) = self.position(name) {
+            self.0[index].away = away;
+        }
+        self
+    }
+
+    pub fn is_away(&self, name: &str) -> bool {
+        self.position(name).is_some_and(|i| self.0[i].away)
+    }
+
+    /// Drivers in the new order given as indices into `all`
+    pub fn reorder(&self, order: &[usize]) -> Self {
+        Drivers(order.iter().map(|i| self.0[*i].clone()).collect())
+    }
+
+    /// `Co-authored-by` trailers for drivers among commit `authors`, given
+    /// as name and email, except `me` who makes the commit
+    pub fn co_authors(&self, authors: &[(String, String)], me: &Driver) -> Vec<String> {
+        let mut trailers: Vec<String> = Vec::new();
+        for (name, email) in authors {
+            let author = Driver::new(name, Some(email.clone()), None);
+            let Some(driver) = self.find(&author) else {
+                continue;
+            };
+            if driver.same_person(me) || author.same_person(me) {
+                continue;
+            }
+            let email = driver.email.as_deref().unwrap_or(email);
+            let trailer = format!("Co-authored-by: {} <{}>", driver.name, email);
+            if !trailers.contains(&trailer) {
+                trailers.push(trailer);
+            }
+        }
+        trailers
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Driver> {
+        self.0.iter()
+    }
+
+    /// Keys of all drivers
     pub fn all(&self) -> Vec<String> {
-        self.0.clone()
+        self.0.iter().map(|d| d.key().to_string()).collect()
+    }
+
+    pub fn present(&self) -> Vec<String> {
+        self.0
+            .iter()
+            .filter(|d| !d.away)
+            .map(|d| d.key().to_string())
+            .collect()
+    }
+}
+
+impl FromIterator<Driver> for Drivers {
+    fn from_iter<I: IntoIterator<Item = Driver>>(drivers: I) -> Self {
+        Drivers(drivers.into_iter().collect())
     }
 }
 
@@ -73,7 +230,7 @@ mod tests {
     #[test]
     fn insert_at_end() {
         let drivers = Drivers::new(vec!["a".to_string(), "b".to_string()]);
-        let driver_added = drivers.insert(Some("b".to_string()), "c");
+        let driver_added = drivers.insert(Some("b".to_string()), Driver::named("c"));
         assert_eq!(
             driver_added.all(),
             vec!["a".to_string(), "b".to_string(), "c".to_string()]
@@ -83,7 +240,7 @@ mod tests {
     #[test]
     fn insert_in_middle() {
         let drivers = Drivers::new(vec!["a".to_string(), "b".to_string()]);
-        let driver_added = drivers.insert(Some("a".to_string()), "c");
+        let driver_added = drivers.insert(Some("a".to_string()), Driver::named("c"));
         assert_eq!(
             driver_added.all(),
             vec!["a".to_string(), "c".to_string(), "b".to_string()]
@@ -103,4 +260,90 @@ mod tests {
         let driver_removed = drivers.remove("b");
         assert_eq!(driver_removed.all(), vec!["a".to_string(), "c".to_string()]);
     }
+
+    #[test]
+    fn next_skips_away() {
+        let drivers = Drivers::new(vec!["a".to_string(), "b".to_string(), "c".to_string()])
+            .set_away("b", true);
+        assert_eq!(drivers.next("a"), Some("c".to_string()));
+        assert_eq!(drivers.prev("c"), Some("a".to_string()));
+        assert_eq!(drivers.all().len(), 3);
+    }
+
+    #[test]
+    fn next_everyone_else_away() {
+        let drivers = Drivers::new(vec!["a".to_string(), "b".to_string()]).set_away("b", true);
+        assert_eq!(drivers.next("a"), Some("a".to_string()));
+    }
+
+    #[test]
+    fn back_keeps_place() {
+        let drivers = Drivers::new(vec!["a".to_string(), "b".to_string(), "c".to_string()])
+            .set_away("b", true)
+            .set_away("b", false);
+        assert_eq!(drivers.next("a"), Some("b".to_string()));
+    }
+
+    #[test]
+    fn same_name_different_email() {
+        let alex = Driver::new("Alex", Some("alex@a.com".to_string()), None);
+        let other_alex = Driver::new("Alex", Some("alex@b.com".to_string()), None);
+        let drivers = Drivers::default()
+            .insert(None, alex)
+            .insert(Some("alex@a.com".to_string()), other_alex);
+
+        assert_eq!(drivers.all(), vec!["alex@a.com", "alex@b.com"]);
+        assert_eq!(drivers.next("alex@a.com"), Some("alex@b.com".to_string()));
+        assert_eq!(drivers.name("alex@b.com"), "Alex <alex@b.com>");
+    }
+
+    #[test]
+    fn identify_plain_name() {
+        let mut drivers = Drivers::new(vec!["a".to_string(), "b".to_string()]);
+        let a = Driver::new("a", Some("a@a.com".to_string()), None);
+
+        assert_eq!(drivers.identify(&a), Some("a".to_string()));
+        assert_eq!(drivers.all(), vec!["a@a.com", "b"]);
+        assert_eq!(drivers.name("a@a.com"), "a");
+        // Old keys in states still find the driver
+        assert!(drivers.contains("a"));
+        assert_eq!(drivers.identify(&a), None);
+    }
+
+    #[test]
+    fn id_wins_over_email() {
+        let work = Driver::new("a", Some("a@work.com".to_string()), Some("1".to_string()));
+        let home = Driver::new("a", Some("a@home.com".to_string()), Some("1".to_string()));
+
+        assert!(work.same_person(&home));
+        assert_eq!(work.key(), "1");
+    }
+
+    #[test]
+    fn co_authors() {
+        let drivers = Drivers::default()
+            .insert(None, Driver::new("Alex", Some("alex@one.com".into()), None))
+            .insert(None, Driver::new("Alex", Some("alex@two.com".into()), None))
+            .insert(None, Driver::named("sam"));
+        let author = |name: &str, email: &str| (name.to_string(), email.to_string());
+        let authors = vec![
+            author("alex", "alex@one.com"),
+            author("Sam", "sam@laptop.local"),
+            author("alex", "ALEX@one.com"),
+            author("Alex", "alex@two.com"),
+            author("sam", "sam@laptop.local"),
+            author("Init", "init@ci.local"),
+        ];
+
+        assert_eq!(
+            drivers.co_authors(
+                &authors,
+                &Driver::new("Alex", Some("alex@two.com".into()), None)
+            ),
+            vec![
+                "Co-authored-by: Alex <alex@one.com>",
+                "Co-authored-by: sam <sam@laptop.local>",
+            ]
+        );
+    }
 }
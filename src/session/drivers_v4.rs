@@ -0,0 +1,24 @@
+use super::v0::DriversV0;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DriverV4 {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub away: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct DriversV4(pub(super) Vec<DriverV4>);
+
+impl From<DriversV0> for DriversV4 {
+    fn from(drivers_v0: DriversV0) -> Self {
+        DriversV4(
+            drivers_v0
+                .0
+                .into_iter()
+                .map(|name| DriverV4 { name, away: false })
+                .collect(),
+        )
+    }
+}
@@ -0,0 +1,50 @@
+use super::drivers::{Driver, Drivers};
+use super::v5::DriversV4;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DriverV6 {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub email: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub away: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct DriversV6(pub(super) Vec<DriverV6>);
+
+/// Names were the keys, they stay the keys until the drivers identify
+impl From<DriversV4> for DriversV6 {
+    fn from(drivers_v4: DriversV4) -> Self {
+        DriversV6(
+            drivers_v4
+                .0
+                .into_iter()
+                .map(|driver| DriverV6 {
+                    name: driver.name,
+                    email: None,
+                    id: None,
+                    away: driver.away,
+                })
+                .collect(),
+        )
+    }
+}
+
+impl From<DriversV6> for Drivers {
+    fn from(drivers_v6: DriversV6) -> Self {
+        drivers_v6
+            .0
+            .into_iter()
+            .map(|driver| Driver {
+                name: driver.name,
+                email: driver.email,
+                id: driver.id,
+                away: driver.away,
+            })
+            .collect()
+    }
+}
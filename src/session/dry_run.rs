@@ -0,0 +1,59 @@
+use super::history::{Event, History};
+use super::latest::Session;
+use super::session_store::{Error, Result, Store, Transition};
+
+/// Loads sessions but only logs what would be saved
+pub struct DryRun<'a> {
+    store: &'a dyn Store,
+}
+
+impl<'a> DryRun<'a> {
+    pub fn new(store: &'a impl Store) -> DryRun<'a> {
+        Self { store }
+    }
+}
+
+impl Store for DryRun<'_> {
+    fn load(&self) -> Result<Session> {
+        self.store.load()
+    }
+
+    fn load_or_default(&self) -> Result<Session> {
+        self.store.load_or_default()
+    }
+
+    fn load_read_only(&self) -> Result<Session> {
+        self.store.load_read_only()
+    }
+
+    fn save(&self, session: Session) -> Result<()> {
+        self.record(session, &[])
+    }
+
+    fn record(&self, session: Session, events: &[Event]) -> Result<()> {
+        log::info!("Would save session state: {:?}", session.state);
+        for event in events {
+            log::info!("Would record: {:?} by {}", event.kind, event.driver);
+        }
+        Ok(())
+    }
+
+    fn transition(&self, session: Session, transition: &Transition) -> Result<Session> {
+        let (session, events) = transition(session).map_err(Error::Transition)?;
+        self.record(session.clone(), &events)?;
+        Ok(session)
+    }
+
+    fn history(&self) -> Result<History> {
+        self.store.history()
+    }
+
+    fn load_all(&self) -> Result<Vec<(String, Session)>> {
+        self.store.load_all()
+    }
+
+    fn clean(&self) -> Result<()> {
+        log::info!("Would remove the session");
+        Ok(())
+    }
+}
@@ -0,0 +1,296 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum EventKind {
+    Start,
+    Next {
+        next: Option<String>,
+        /// The mob branch before `mob next` committed the work, the same as
+        /// the commit of the event if there was nothing to commit
+        #[serde(default)]
+        before: Option<String>,
+        /// Minutes of the turn, `mob start N` may differ from the settings
+        #[serde(default)]
+        work_duration: Option<i64>,
+    },
+    TakeOver {
+        from: String,
+    },
+    Remove {
+        removed: String,
+    },
+    Join,
+    Leave,
+    Skip {
+        next: Option<String>,
+    },
+    Pause {
+        reason: Option<String>,
+    },
+    Resume,
+    /// Commits from the base branch brought into the mob branch
+    SyncBase {
+        commits: usize,
+    },
+    /// The driver took back their handover before anyone started
+    Undo,
+    Done,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Event {
+    pub time: DateTime<Utc>,
+    pub driver: String,
+    #[serde(flatten)]
+    pub kind: EventKind,
+    /// Commit of the mob branch when the event happened
+    pub commit: Option<String>,
+}
+
+impl Event {
+    pub fn new(driver: &str, kind: EventKind, commit: Option<String>) -> Self {
+        Self {
+            time: Utc::now(),
+            driver: driver.to_string(),
+            kind,
+            commit,
+        }
+    }
+}
+
+/// Append-only log of events, stored as one json object per line
+#[derive(Debug, Clone, Default)]
+pub struct History(Vec<Event>);
+
+/// A turn reconstructed from the history
+#[derive(Debug, Clone, PartialEq)]
+pub struct Turn {
+    pub driver: String,
+    pub started: DateTime<Utc>,
+    /// None while the turn is ongoing
+    pub ended: Option<DateTime<Utc>>,
+    pub from_commit: Option<String>,
+    pub to_commit: Option<String>,
+}
+
+impl History {
+    pub fn parse(data: &[u8]) -> Self {
+        let events = data
+            .split(|b| *b == b'\n')
+            .filter(|line| !line.is_empty())
+            .filter_map(|line| {
+                serde_json::from_slice(line)
+                    .map_err(|err| log::trace!("Skipping unknown history event: {}", err))
+                    .ok()
+            })
+            .collect();
+        History(events)
+    }
+
+    pub fn serialize(events: &[Event]) -> serde_json::Result<Vec<u8>> {
+        let mut data = Vec::new();
+        for event in events {
+            serde_json::to_writer(&mut data, event)?;
+            data.push(b'\n');
+        }
+        Ok(data)
+    }
+
+    pub fn events(&self) -> &[Event] {
+        &self.0
+    }
+
+    /// Turns in chronological order
+    pub fn turns(&self) -> Vec<Turn> {
+        let mut turns: Vec<Turn> = Vec::new();
+        let mut current: Option<Turn> = None;
+
+        for event in &self.0 {
+            match &event.kind {
+                EventKind::Start => {
+                    // Someone took over, the work of the previous turn is unknown
+                    if let Some(turn) = current.take() {
+                        turns.push(Turn {
+                            ended: Some(event.time),
+                            ..turn
+                        });
+                    }
+                    current = Some(Turn {
+                        driver: event.driver.clone(),
+                        started: event.time,
+                        ended: None,
+                        from_commit: event.commit.clone(),
+                        to_commit: None,
+                    });
+                }
+                EventKind::Next { .. } | EventKind::Done => {
+                    let ends_turn = match (&event.kind, &current) {
+                        (EventKind::Done, Some(_)) => true,
+                        (_, Some(turn)) => turn.driver == event.driver,
+                        (_, None) => false,
+                    };
+                    if ends_turn && let Some(turn) = current.take() {
+                        turns.push(Turn {
+                            ended: Some(event.time),
+                            to_commit: event.commit.clone(),
+                            ..turn
+                        });
+                    }
+                }
+                EventKind::Undo => {
+                    // The handed over turn goes on
+                    if current.is_none()
+                        && let Some(turn) = turns.pop_if(|turn| turn.driver == event.driver)
+                    {
+                        current = Some(Turn {
+                            ended: None,
+                            to_commit: None,
+                            ..turn
+                        });
+                    }
+                }
+                EventKind::TakeOver { .. }
+                | EventKind::Remove { .. }
+                | EventKind::Join
+                | EventKind::Leave
+                | EventKind::Skip { .. }
+                | EventKind::Pause { .. }
+                | EventKind::Resume
+                | EventKind::SyncBase { .. } => {}
+            }
+        }
+        turns.extend(current);
+        turns
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn roundtrip() {
+        let events = vec![
+            Event::new("one", EventKind::Start, Some("abc".to_string())),
+            Event::new(
+                "one",
+                EventKind::Next {
+                    next: Some("two".to_string()),
+                    before: Some("abc".to_string()),
+                    work_duration: Some(10),
+                },
+                Some("def".to_string()),
+            ),
+        ];
+
+        let data = History::serialize(&events).unwrap();
+        assert_eq!(History::parse(&data).events(), events.as_slice());
+    }
+
+    fn event(driver: &str, minute: u32, kind: EventKind, commit: &str) -> Event {
+        Event {
+            time: format!("2021-09-10T14:{:02}:00Z", minute).parse().unwrap(),
+            driver: driver.to_string(),
+            kind,
+            commit: Some(commit.to_string()),
+        }
+    }
+
+    #[test]
+    fn turns() {
+        let history = History(vec![
+            event("one", 0, EventKind::Start, "a"),
+            event(
+                "one",
+                10,
+                EventKind::Next {
+                    next: None,
+                    before: None,
+                    work_duration: None,
+                },
+                "b",
+            ),
+            event("two", 12, EventKind::Start, "b"),
+            event("two", 20, EventKind::Done, "c"),
+        ]);
+
+        let turns = history.turns();
+        assert_eq!(turns.len(), 2);
+        assert_eq!(turns[0].driver, "one");
+        assert_eq!(turns[0].from_commit, Some("a".to_string()));
+        assert_eq!(turns[0].to_commit, Some("b".to_string()));
+        assert_eq!(turns[1].driver, "two");
+        assert_eq!(turns[1].to_commit, Some("c".to_string()));
+        assert_eq!(turns[1].ended, Some(history.events()[3].time));
+    }
+
+    #[test]
+    fn turns_take_over() {
+        let history = History(vec![
+            event("one", 0, EventKind::Start, "a"),
+            event(
+                "two",
+                30,
+                EventKind::TakeOver {
+                    from: "one".to_string(),
+                },
+                "a",
+            ),
+            event("two", 30, EventKind::Start, "a"),
+        ]);
+
+        let turns = history.turns();
+        assert_eq!(turns.len(), 2);
+        assert_eq!(turns[0].ended, Some(history.events()[2].time));
+        assert_eq!(turns[0].to_commit, None);
+        assert_eq!(turns[1].driver, "two");
+        assert_eq!(turns[1].ended, None);
+    }
+
+    #[test]
+    fn turns_undo() {
+        let history = History(vec![
+            event("one", 0, EventKind::Start, "a"),
+            event(
+                "one",
+                10,
+                EventKind::Next {
+                    next: None,
+                    before: None,
+                    work_duration: None,
+                },
+                "b",
+            ),
+            event("one", 11, EventKind::Undo, "b"),
+            event(
+                "one",
+                15,
+                EventKind::Next {
+                    next: None,
+                    before: None,
+                    work_duration: None,
+                },
+                "c",
+            ),
+        ]);
+
+        let turns = history.turns();
+        assert_eq!(turns.len(), 1);
+        assert_eq!(turns[0].from_commit, Some("a".to_string()));
+        assert_eq!(turns[0].to_commit, Some("c".to_string()));
+        assert_eq!(turns[0].ended, Some(history.events()[3].time));
+    }
+
+    #[test]
+    fn skip_unknown_events() {
+        let data = br#"{"time":"2021-09-10T14:22:41Z","driver":"one","event":"start","commit":null}
+{"time":"2021-09-10T14:32:41Z","driver":"one","event":"from_the_future","commit":null}
+"#;
+
+        let history = History::parse(data);
+        assert_eq!(history.events().len(), 1);
+        assert_eq!(history.events()[0].kind, EventKind::Start);
+    }
+}
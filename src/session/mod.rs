@@ -1,33 +1,109 @@
 mod branches;
+mod branches_v0;
 mod drivers;
+mod drivers_v0;
+mod drivers_v4;
+mod drivers_v6;
+mod dry_run;
+mod history;
+mod rotation;
 #[allow(clippy::module_inception)]
 mod session;
 mod session_store;
 mod session_v0;
+mod session_v1;
+mod session_v2;
+mod session_v3;
+mod session_v4;
+mod session_v5;
+mod session_v6;
 mod settings;
 mod settings_v0;
+mod settings_v1;
+mod settings_v3;
 mod state;
 mod state_v0;
+mod state_v1;
+mod state_v2;
+mod state_v3;
+mod state_v5;
 mod versioned_session;
 
 pub mod v0 {
     use super::*;
-    pub use branches::Branches;
-    pub use drivers::Drivers;
+    pub use branches_v0::BranchesV0;
+    pub use drivers_v0::DriversV0;
     pub use session_v0::SessionV0;
     pub use settings_v0::SettingsV0;
     pub use state_v0::StateV0;
 }
 
+pub mod v1 {
+    use super::*;
+    pub use branches_v0::BranchesV0;
+    pub use drivers_v0::DriversV0;
+    pub use session_v1::SessionV1;
+    pub use settings_v1::SettingsV1;
+    pub use state_v1::StateV1;
+}
+
+pub mod v2 {
+    use super::*;
+    pub use branches_v0::BranchesV0;
+    pub use drivers_v0::DriversV0;
+    pub use session_v2::SessionV2;
+    pub use settings_v1::SettingsV1;
+    pub use state_v2::StateV2;
+}
+
+pub mod v3 {
+    use super::*;
+    pub use branches_v0::BranchesV0;
+    pub use drivers_v0::DriversV0;
+    pub use session_v3::SessionV3;
+    pub use settings_v3::SettingsV3;
+    pub use state_v3::StateV3;
+}
+
+pub mod v4 {
+    use super::*;
+    pub use branches_v0::BranchesV0;
+    pub use drivers_v4::DriversV4;
+    pub use session_v4::SessionV4;
+    pub use settings_v3::SettingsV3;
+    pub use state_v3::StateV3;
+}
+
+pub mod v5 {
+    use super::*;
+    pub use branches_v0::BranchesV0;
+    pub use drivers_v4::DriversV4;
+    pub use session_v5::SessionV5;
+    pub use settings_v3::SettingsV3;
+    pub use state_v5::StateV5;
+}
+
+pub mod v6 {
+    use super::*;
+    pub use branches_v0::BranchesV0;
+    pub use drivers_v6::DriversV6;
+    pub use session_v6::SessionV6;
+    pub use settings_v3::SettingsV3;
+    pub use state_v5::StateV5;
+}
+
 pub mod latest {
     use super::*;
     pub use branches::Branches;
-    pub use drivers::Drivers;
+    pub use drivers::{Driver, Drivers};
     pub use session::Session;
-    pub use settings::Settings;
-    pub use state::State;
+    pub use settings::{DoneStrategy, Navigator, Settings};
+    pub use state::{HEARTBEAT_INTERVAL, LEASE_DURATION, State};
 }
 
+pub use dry_run::DryRun;
+pub use history::{Event, EventKind, History};
 pub use latest::*;
-pub use session_store::{SessionStore, Store};
+pub use rotation::Rotation;
+pub use session_store::{SessionStore, Store, Transition, ensure_unchanged};
 pub use versioned_session::VersionedSession;
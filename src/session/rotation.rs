@@ -0,0 +1,179 @@
+use super::drivers::Drivers;
+use super::history::History;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::hash::{BuildHasher, RandomState};
+
+/// How the next driver is chosen
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+pub enum Rotation {
+    /// In the order of drivers
+    #[default]
+    RoundRobin,
+    /// Random, but nobody drives twice before everyone has driven
+    Random,
+    /// Whoever drove least recently
+    LeastRecent,
+    /// The driver picks the next driver at handover
+    Pick,
+}
+
+impl Rotation {
+    pub const ALL: [Rotation; 4] = [
+        Rotation::RoundRobin,
+        Rotation::Random,
+        Rotation::LeastRecent,
+        Rotation::Pick,
+    ];
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            Rotation::RoundRobin => "Round robin",
+            Rotation::Random => "Random without repeats",
+            Rotation::LeastRecent => "Least recently driven",
+            Rotation::Pick => "Pick at handover",
+        }
+    }
+
+    /// Next driver after `current`. Picking at handover falls back to
+    /// round robin, the caller is expected to ask.
+    pub fn next(&self, drivers: &Drivers, current: &str, history: &History) -> Option<String> {
+        match self {
+            Rotation::RoundRobin | Rotation::Pick => drivers.next(current),
+            Rotation::Random => Rotation::random(
+                drivers,
+                current,
+                history,
+                RandomState::new().hash_one(current),
+            ),
+            Rotation::LeastRecent => Rotation::least_recent(drivers, current, history),
+        }
+    }
+
+    fn candidates(drivers: &Drivers, current: &str) -> Vec<String> {
+        let candidates: Vec<String> = drivers
+            .present()
+            .into_iter()
+            .filter(|driver| driver != current)
+            .collect();
+        if candidates.is_empty() {
+            return drivers.next(current).into_iter().collect();
+        }
+        candidates
+    }
+
+    fn random(drivers: &Drivers, current: &str, history: &History, seed: u64) -> Option<String> {
+        let mut turns: HashMap<String, usize> = HashMap::new();
+        for turn in history.turns() {
+            *turns
+                .entry(Rotation::key(drivers, turn.driver))
+                .or_default() += 1;
+        }
+
+        let candidates = Rotation::candidates(drivers, current);
+        let fewest = candidates
+            .iter()
+            .map(|driver| turns.get(driver).copied().unwrap_or_default())
+            .min()?;
+        let candidates: Vec<&String> = candidates
+            .iter()
+            .filter(|driver| turns.get(*driver).copied().unwrap_or_default() == fewest)
+            .collect();
+
+        let index = (seed % candidates.len() as u64) as usize;
+        Some(candidates[index].clone())
+    }
+
+    fn least_recent(drivers: &Drivers, current: &str, history: &History) -> Option<String> {
+        let mut last_turn = HashMap::new();
+        for turn in history.turns() {
+            last_turn.insert(Rotation::key(drivers, turn.driver), turn.started);
+        }
+
+        // Never driven sorts first, ties keep the order of drivers
+        Rotation::candidates(drivers, current)
+            .into_iter()
+            .min_by_key(|driver| last_turn.get(driver).copied())
+    }
+
+    /// Turns recorded before a driver had an email or id use their name
+    fn key(drivers: &Drivers, driver: String) -> String {
+        match drivers.get(&driver) {
+            Some(found) => found.key().to_string(),
+            None => driver,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::session::{Event, EventKind};
+    use chrono::{Duration, Utc};
+
+    fn drivers() -> Drivers {
+        Drivers::new(vec!["a".to_string(), "b".to_string(), "c".to_string()])
+    }
+
+    fn history(drivers: &[&str]) -> History {
+        let start = Utc::now();
+        let events: Vec<Event> = drivers
+            .iter()
+            .enumerate()
+            .map(|(i, driver)| Event {
+                time: start + Duration::minutes(i as i64),
+                ..Event::new(driver, EventKind::Start, None)
+            })
+            .collect();
+        History::parse(&History::serialize(&events).unwrap())
+    }
+
+    #[test]
+    fn random_without_repeat() {
+        let history = history(&["a", "b"]);
+        for seed in 0..10 {
+            assert_eq!(
+                Rotation::random(&drivers(), "b", &history, seed),
+                Some("c".to_string())
+            );
+        }
+    }
+
+    #[test]
+    fn random_never_current() {
+        let history = history(&["a", "b", "c"]);
+        for seed in 0..10 {
+            assert_ne!(
+                Rotation::random(&drivers(), "c", &history, seed),
+                Some("c".to_string())
+            );
+        }
+    }
+
+    #[test]
+    fn random_alone() {
+        let drivers = Drivers::new(vec!["a".to_string()]);
+        assert_eq!(
+            Rotation::random(&drivers, "a", &History::default(), 3),
+            Some("a".to_string())
+        );
+    }
+
+    #[test]
+    fn least_recent_never_driven() {
+        let history = history(&["a", "b"]);
+        assert_eq!(
+            Rotation::least_recent(&drivers(), "b", &history),
+            Some("c".to_string())
+        );
+    }
+
+    #[test]
+    fn least_recent_oldest() {
+        let history = history(&["c", "a", "b"]);
+        assert_eq!(
+            Rotation::least_recent(&drivers(), "b", &history),
+            Some("c".to_string())
+        );
+    }
+}
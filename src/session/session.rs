@@ -1,14 +1,30 @@
+use super::history::History;
 use super::latest::*;
-use super::v0::{SessionV0, StateV0};
+use super::v6::SessionV6;
+use chrono::{DateTime, Duration, Local, TimeZone, Utc};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::default::Default;
 
+type DurationMinutes = i64;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Session {
     pub drivers: Drivers,
     pub branches: Branches,
     pub settings: Option<Settings>,
     pub state: State,
+    pub last_break: Option<DateTime<Utc>>,
+    /// Mob time since the last break
+    pub mob_time: DurationMinutes,
+    #[serde(default)]
+    pub navigator: Option<String>,
+    /// Session fields added by newer versions, written back untouched
+    #[serde(flatten)]
+    pub extra: BTreeMap<String, serde_json::Value>,
+    /// Set when loaded read-only from a newer version than this client knows
+    #[serde(skip)]
+    pub newer_version: Option<String>,
 }
 
 impl Default for Session {
@@ -18,46 +34,140 @@ impl Default for Session {
             branches: Branches::default(),
             settings: None,
             state: State::Stopped,
+            last_break: None,
+            mob_time: 0,
+            navigator: None,
+            extra: BTreeMap::new(),
+            newer_version: None,
         }
     }
 }
 
-impl From<SessionV0> for Session {
-    fn from(session_v0: SessionV0) -> Self {
+impl From<SessionV6> for Session {
+    fn from(session_v6: SessionV6) -> Self {
         Self {
-            drivers: session_v0.drivers,
-            branches: session_v0.branches,
-            settings: match session_v0.settings {
-                Some(settings) => Some(Settings {
-                    commit_message: settings.commit_message,
-                    work_duration: settings.work_duration,
-                }),
-                None => None,
-            },
-            state: match session_v0.state {
-                StateV0::Stopped => State::Stopped,
-                StateV0::Working { driver } => State::Working { driver },
-                StateV0::WaitingForNext { next, .. } => State::WaitingForNext { next },
-            },
+            drivers: session_v6.drivers.into(),
+            branches: session_v6.branches.into(),
+            settings: session_v6.settings.map(Settings::from),
+            state: session_v6.state.into(),
+            last_break: session_v6.last_break,
+            mob_time: session_v6.mob_time,
+            navigator: session_v6.navigator,
+            extra: session_v6.extra,
+            newer_version: None,
         }
     }
 }
 
 impl Session {
+    /// A new session, named sessions get a mob branch of their own
+    pub fn named(name: &str) -> Self {
+        Session {
+            branches: Branches::for_session(name),
+            ..Session::default()
+        }
+    }
+
     pub fn get_driver(&self) -> Option<String> {
-        match &self.state {
-            State::Working { driver } => Some(driver.clone()),
-            State::WaitingForNext { next, .. } => {
+        match self.state.active() {
+            State::Working { driver, .. } => Some(driver.clone()),
+            State::WaitingForNext { next, .. } | State::Break { next, .. } => {
                 next.clone().and_then(|d| self.drivers.prev(d.as_str()))
             }
-            State::Stopped => None,
+            State::Stopped | State::Paused { .. } => None,
+        }
+    }
+
+    /// Who navigates while `driver` drives, the next driver is whoever
+    /// the rotation picks after `driver`
+    pub fn navigator_for(&self, driver: &str, history: &History) -> Option<String> {
+        let settings = self.settings.clone().unwrap_or_default();
+        match settings.navigator {
+            Navigator::NextDriver => settings.rotation.next(&self.drivers, driver, history),
+            Navigator::PreviousDriver => self.drivers.prev(driver),
+            Navigator::Nobody => None,
+        }
+        .filter(|navigator| navigator != driver)
+    }
+
+    /// Updates the identity of `driver` if they are part of the mob, keys
+    /// of the driver in the state are updated to match
+    pub fn identify(mut self, driver: &Driver) -> Self {
+        let Some(previous) = self.drivers.identify(driver) else {
+            return self;
+        };
+        let key = match self.drivers.find(driver) {
+            Some(driver) => driver.key().to_string(),
+            None => return self,
+        };
+        Session {
+            state: self.state.rename(&previous, &key),
+            navigator: self.navigator.map(|navigator| {
+                if navigator == previous {
+                    key.clone()
+                } else {
+                    navigator
+                }
+            }),
+            ..self
+        }
+    }
+
+    /// Recompute the navigator for whoever drives now or next
+    pub fn with_navigator(self, history: &History) -> Self {
+        let driver = match self.state.active() {
+            State::Working { driver, .. } => Some(driver.clone()),
+            State::WaitingForNext { next } | State::Break { next, .. } => next.clone(),
+            State::Stopped | State::Paused { .. } => None,
+        };
+        Session {
+            navigator: driver.and_then(|driver| self.navigator_for(&driver, history)),
+            ..self
+        }
+    }
+
+    /// Returns when the break should end if it's time for one
+    pub fn break_due(&self, now: DateTime<Local>) -> Option<DateTime<Utc>> {
+        let settings = self.settings.as_ref()?;
+
+        if let Some(lunch_end) = self.lunch_due(settings, now) {
+            return Some(lunch_end);
         }
+
+        if settings.break_interval > 0 && self.mob_time >= settings.break_interval {
+            let until = now + Duration::minutes(settings.break_duration);
+            return Some(until.with_timezone(&Utc));
+        }
+        None
+    }
+
+    fn lunch_due(&self, settings: &Settings, now: DateTime<Local>) -> Option<DateTime<Utc>> {
+        let (lunch_start, lunch_end) = settings.lunch()?;
+
+        let time = now.time();
+        if time < lunch_start || time >= lunch_end {
+            return None;
+        }
+
+        let today = now.date_naive();
+        let had_lunch = self.last_break.is_some_and(|last_break| {
+            last_break.with_timezone(&Local).naive_local() >= today.and_time(lunch_start)
+        });
+        if had_lunch {
+            return None;
+        }
+
+        Local
+            .from_local_datetime(&today.and_time(lunch_end))
+            .single()
+            .map(|until| until.with_timezone(&Utc))
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::session::Rotation;
 
     #[test]
     fn get_driver_one() {
@@ -75,6 +185,9 @@ mod tests {
             drivers: Drivers::new(vec!["one".to_string(), "two".to_string()]),
             state: State::Working {
                 driver: "two".to_string(),
+                started: Utc::now(),
+                work_duration: 10,
+                last_seen: None,
             },
             ..Session::default()
         };
@@ -94,4 +207,139 @@ mod tests {
 
         assert_eq!(session.get_driver(), Some("one".to_string()))
     }
+
+    #[test]
+    fn navigator_next_driver() {
+        let session = Session {
+            drivers: Drivers::new(vec![
+                "one".to_string(),
+                "two".to_string(),
+                "three".to_string(),
+            ]),
+            ..Session::default()
+        };
+
+        assert_eq!(
+            session.navigator_for("three", &History::default()),
+            Some("one".to_string())
+        )
+    }
+
+    #[test]
+    fn navigator_previous_driver() {
+        let session = Session {
+            drivers: Drivers::new(vec![
+                "one".to_string(),
+                "two".to_string(),
+                "three".to_string(),
+            ]),
+            settings: Some(Settings {
+                navigator: Navigator::PreviousDriver,
+                ..Settings::default()
+            }),
+            ..Session::default()
+        };
+
+        assert_eq!(
+            session.navigator_for("three", &History::default()),
+            Some("two".to_string())
+        )
+    }
+
+    #[test]
+    fn navigator_follows_rotation() {
+        let session = Session {
+            drivers: Drivers::new(vec![
+                "one".to_string(),
+                "two".to_string(),
+                "three".to_string(),
+            ]),
+            settings: Some(Settings {
+                rotation: Rotation::LeastRecent,
+                ..Settings::default()
+            }),
+            ..Session::default()
+        };
+        let history = History::parse(
+            br#"{"time":"2021-09-10T14:00:00Z","driver":"two","event":"start","commit":null}
+{"time":"2021-09-10T14:10:00Z","driver":"three","event":"start","commit":null}
+{"time":"2021-09-10T14:20:00Z","driver":"one","event":"start","commit":null}
+"#,
+        );
+
+        assert_eq!(
+            session.navigator_for("one", &history),
+            Some("two".to_string())
+        )
+    }
+
+    #[test]
+    fn navigator_alone() {
+        let session = Session {
+            drivers: Drivers::new(vec!["one".to_string()]),
+            ..Session::default()
+        };
+
+        assert_eq!(session.navigator_for("one", &History::default()), None)
+    }
+
+    fn lunch_settings() -> Settings {
+        Settings {
+            lunch_start: Some("11:30".to_string()),
+            lunch_end: Some("12:30".to_string()),
+            ..Settings::default()
+        }
+    }
+
+    #[test]
+    fn break_due_after_interval() {
+        let now = Local.with_ymd_and_hms(2021, 9, 10, 9, 0, 0).unwrap();
+        let session = Session {
+            settings: Some(Settings::default()),
+            mob_time: 55,
+            ..Session::default()
+        };
+
+        assert_eq!(
+            session.break_due(now),
+            Some((now + Duration::minutes(5)).with_timezone(&Utc))
+        );
+    }
+
+    #[test]
+    fn break_not_due() {
+        let now = Local.with_ymd_and_hms(2021, 9, 10, 9, 0, 0).unwrap();
+        let session = Session {
+            settings: Some(lunch_settings()),
+            mob_time: 54,
+            ..Session::default()
+        };
+
+        assert_eq!(session.break_due(now), None);
+    }
+
+    #[test]
+    fn break_due_for_lunch() {
+        let now = Local.with_ymd_and_hms(2021, 9, 10, 11, 45, 0).unwrap();
+        let session = Session {
+            settings: Some(lunch_settings()),
+            ..Session::default()
+        };
+
+        let lunch_end = Local.with_ymd_and_hms(2021, 9, 10, 12, 30, 0).unwrap();
+        assert_eq!(session.break_due(now), Some(lunch_end.with_timezone(&Utc)));
+    }
+
+    #[test]
+    fn lunch_only_once() {
+        let now = Local.with_ymd_and_hms(2021, 9, 10, 12, 15, 0).unwrap();
+        let lunch = Local.with_ymd_and_hms(2021, 9, 10, 11, 35, 0).unwrap();
+        let session = Session {
+            settings: Some(lunch_settings()),
+            last_break: Some(lunch.with_timezone(&Utc)),
+            ..Session::default()
+        };
+
+        assert_eq!(session.break_due(now), None);
+    }
 }
@@ -1,11 +1,19 @@
 use super::VersionedSession;
-use super::latest::Session;
+use super::history::{Event, History};
+use super::latest::{Session, State};
 use super::versioned_session::Versioned;
 use crate::git::{self, store};
 use crate::session::v0::SessionV0;
+use anyhow::anyhow;
 
 pub type Result<T, E = Error> = core::result::Result<T, E>;
 
+/// Computes the next session and the events that led to it
+pub type Transition<'t> = dyn Fn(Session) -> anyhow::Result<(Session, Vec<Event>)> + 't;
+
+/// Attempts to save a transition when others save at the same time
+const SAVE_ATTEMPTS: usize = 3;
+
 #[derive(thiserror::Error, Debug)]
 pub enum Error {
     #[error("Could not fetch session. Have you run start? Is the git server down?")]
@@ -16,12 +24,39 @@ pub enum Error {
 
     #[error("you're version is to old, upgrade to version `{0}`")]
     NewerVersion(String),
+
+    #[error(transparent)]
+    Transition(anyhow::Error),
+
+    #[error("someone else changed the session at the same time, {0}")]
+    Conflict(anyhow::Error),
+}
+
+/// Fails transitions decided on `state` if the session has moved on
+pub fn ensure_unchanged(state: &State, session: &Session) -> anyhow::Result<()> {
+    if session.state.same_as(state) {
+        Ok(())
+    } else {
+        Err(anyhow!("run 'mob status' to see what changed"))
+    }
 }
 
 pub trait Store {
     fn load(&self) -> Result<Session>;
     fn load_or_default(&self) -> Result<Session>;
+    /// Like `load_or_default` but also reads sessions written by newer
+    /// versions, as far as they are understood. They can't be saved.
+    fn load_read_only(&self) -> Result<Session>;
     fn save(&self, session: Session) -> Result<()>;
+    /// Save session and append events to the history
+    fn record(&self, session: Session, events: &[Event]) -> Result<()>;
+    /// Record the result of `transition` from `session`. If someone else
+    /// saved first it is applied again to their session, and fails with
+    /// `Error::Conflict` if it no longer applies.
+    fn transition(&self, session: Session, transition: &Transition) -> Result<Session>;
+    fn history(&self) -> Result<History>;
+    /// All sessions in the repository by name
+    fn load_all(&self) -> Result<Vec<(String, Session)>>;
     fn clean(&self) -> Result<()>;
 }
 
@@ -34,20 +69,35 @@ impl<'a> SessionStore<'a> {
         SessionStore { store }
     }
 
-    fn get_session(data: Vec<u8>) -> Result<Session> {
-        match serde_json::from_slice::<VersionedSession>(data.as_slice()) {
-            Ok(versioned_session) => Ok(versioned_session.latest()),
-            Err(..) => {
-                let versioned = serde_json::from_slice::<Versioned>(data.as_slice())?;
-                match versioned.version {
-                    Some(version) => Err(Error::NewerVersion(version)),
-                    None => {
-                        // Assume first unversioned
-                        let session = serde_json::from_slice::<SessionV0>(data.as_slice())?;
-                        Ok(VersionedSession::V0(session).latest())
-                    }
-                }
+    fn load_or_default_session(&self, read_only: bool) -> Result<Session> {
+        match self.store.load() {
+            Ok(data) => SessionStore::get_session(data, read_only),
+            Err(store::Error::Missing { source }) => {
+                log::trace!("No session: {source:?}. Returning default");
+                Ok(Session::named(self.store.session()))
             }
+            Err(err) => Err(Error::from(err)),
+        }
+    }
+
+    /// Sessions written by newer clients are only loaded if `read_only`
+    fn get_session(data: Vec<u8>, read_only: bool) -> Result<Session> {
+        let versioned = serde_json::from_slice::<Versioned>(data.as_slice())?;
+        match versioned.version {
+            // Assume first unversioned
+            None => {
+                let session = serde_json::from_slice::<SessionV0>(data.as_slice())?;
+                Ok(VersionedSession::V0(session).latest())
+            }
+            Some(version) if Versioned::is_known(&version) => {
+                let versioned_session = serde_json::from_slice::<VersionedSession>(&data)?;
+                Ok(versioned_session.latest())
+            }
+            Some(version) if read_only => VersionedSession::newer(&data).map_err(|err| {
+                log::trace!("Could not read newer session: {}", err);
+                Error::NewerVersion(version)
+            }),
+            Some(version) => Err(Error::NewerVersion(version)),
         }
     }
 }
@@ -57,25 +107,74 @@ impl<'a> Store for SessionStore<'a> {
         self.store
             .load()
             .map_err(Error::Store)
-            .and_then(SessionStore::get_session)
+            .and_then(|data| SessionStore::get_session(data, false))
     }
 
     fn load_or_default(&self) -> Result<Session> {
-        match self.store.load() {
-            Ok(data) => SessionStore::get_session(data),
+        self.load_or_default_session(false)
+    }
+
+    fn load_read_only(&self) -> Result<Session> {
+        self.load_or_default_session(true)
+    }
+
+    fn save(&self, session: Session) -> Result<()> {
+        self.record(session, &[])
+    }
+
+    fn record(&self, session: Session, events: &[Event]) -> Result<()> {
+        if let Some(version) = session.newer_version {
+            return Err(Error::NewerVersion(version));
+        }
+        let versioned_session = VersionedSession::from_latest(session);
+        let json = serde_json::to_vec_pretty(&versioned_session)?;
+        let history = History::serialize(events)?;
+        self.store.save(&json, &history)?;
+        Ok(())
+    }
+
+    fn transition(&self, session: Session, transition: &Transition) -> Result<Session> {
+        let mut session = session;
+        let mut attempt = 1;
+        loop {
+            let (next, events) = transition(session).map_err(|err| match attempt {
+                1 => Error::Transition(err),
+                _ => Error::Conflict(err),
+            })?;
+            match self.record(next.clone(), &events) {
+                Err(Error::Store(store::Error::Conflict)) if attempt < SAVE_ATTEMPTS => {
+                    log::warn!("Someone else changed the session, trying again");
+                    session = self.load()?;
+                    attempt += 1;
+                }
+                Err(Error::Store(store::Error::Conflict)) => {
+                    return Err(Error::Conflict(anyhow!(
+                        "gave up after {} attempts",
+                        SAVE_ATTEMPTS
+                    )));
+                }
+                result => return result.map(|_| next),
+            }
+        }
+    }
+
+    fn history(&self) -> Result<History> {
+        match self.store.load_history() {
+            Ok(data) => Ok(History::parse(&data)),
             Err(store::Error::Missing { source }) => {
-                log::trace!("No session: {source:?}. Returning default");
-                Ok(Session::default())
+                log::trace!("No history: {source:?}. Returning empty");
+                Ok(History::default())
             }
             Err(err) => Err(Error::from(err)),
         }
     }
 
-    fn save(&self, session: Session) -> Result<()> {
-        let versioned_session = VersionedSession::V1(session);
-        let json = serde_json::to_vec_pretty(&versioned_session)?;
-        self.store.save(&json)?;
-        Ok(())
+    fn load_all(&self) -> Result<Vec<(String, Session)>> {
+        self.store
+            .list()?
+            .into_iter()
+            .map(|(name, data)| Ok((name, SessionStore::get_session(data, true)?)))
+            .collect()
     }
 
     fn clean(&self) -> Result<()> {
@@ -88,23 +187,132 @@ impl<'a> Store for SessionStore<'a> {
 mod tests {
     use super::*;
     use crate::git;
+    use crate::session::{Driver, Drivers, State};
 
     struct MockStore {
         pub load_data: Vec<u8>,
     }
 
     impl git::Store for MockStore {
+        fn session(&self) -> &str {
+            git::store::DEFAULT_SESSION
+        }
         fn load(&self) -> std::result::Result<Vec<u8>, git::store::Error> {
             Ok(self.load_data.clone())
         }
         fn clean(&self) -> std::result::Result<(), git::store::Error> {
             todo!()
         }
-        fn save(&self, _: &[u8]) -> std::result::Result<(), git::store::Error> {
+        fn save(&self, _: &[u8], _: &[u8]) -> std::result::Result<(), git::store::Error> {
+            todo!()
+        }
+        fn load_history(&self) -> std::result::Result<Vec<u8>, git::store::Error> {
+            todo!()
+        }
+        fn list(&self) -> std::result::Result<Vec<(String, Vec<u8>)>, git::store::Error> {
+            todo!()
+        }
+    }
+
+    /// Rejects the first `conflicts` saves like a push after someone else's
+    struct ConflictStore {
+        load_data: Vec<u8>,
+        conflicts: std::cell::Cell<usize>,
+    }
+
+    impl git::Store for ConflictStore {
+        fn session(&self) -> &str {
+            git::store::DEFAULT_SESSION
+        }
+        fn load(&self) -> std::result::Result<Vec<u8>, git::store::Error> {
+            Ok(self.load_data.clone())
+        }
+        fn clean(&self) -> std::result::Result<(), git::store::Error> {
+            todo!()
+        }
+        fn save(&self, _: &[u8], _: &[u8]) -> std::result::Result<(), git::store::Error> {
+            match self.conflicts.get() {
+                0 => Ok(()),
+                conflicts => {
+                    self.conflicts.set(conflicts - 1);
+                    Err(git::store::Error::Conflict)
+                }
+            }
+        }
+        fn load_history(&self) -> std::result::Result<Vec<u8>, git::store::Error> {
+            todo!()
+        }
+        fn list(&self) -> std::result::Result<Vec<(String, Vec<u8>)>, git::store::Error> {
             todo!()
         }
     }
 
+    fn conflict_store(session: Session, conflicts: usize) -> ConflictStore {
+        ConflictStore {
+            load_data: serde_json::to_vec(&VersionedSession::from_latest(session)).unwrap(),
+            conflicts: std::cell::Cell::new(conflicts),
+        }
+    }
+
+    #[test]
+    fn transition_retries_on_conflict() {
+        let theirs = Session {
+            drivers: Drivers::new(vec!["alice".to_string(), "bob".to_string()]),
+            ..Session::default()
+        };
+        let store = conflict_store(theirs, 1);
+        let session_store = SessionStore::new(&store);
+
+        let mine = Session {
+            drivers: Drivers::new(vec!["alice".to_string()]),
+            ..Session::default()
+        };
+        let attempts = std::cell::Cell::new(0);
+        let session = session_store
+            .transition(mine, &|session| {
+                attempts.set(attempts.get() + 1);
+                let drivers = session
+                    .drivers
+                    .clone()
+                    .insert(None, Driver::new("carol", None, None));
+                Ok((Session { drivers, ..session }, vec![]))
+            })
+            .unwrap();
+
+        assert_eq!(attempts.get(), 2);
+        assert_eq!(session.drivers.all(), vec!["alice", "bob", "carol"]);
+    }
+
+    #[test]
+    fn transition_no_longer_applies() {
+        let theirs = Session {
+            state: State::Working {
+                driver: "bob".to_string(),
+                started: chrono::Utc::now(),
+                work_duration: 10,
+                last_seen: None,
+            },
+            ..Session::default()
+        };
+        let store = conflict_store(theirs, 1);
+        let session_store = SessionStore::new(&store);
+
+        let result = session_store.transition(Session::default(), &|session| {
+            ensure_unchanged(&State::Stopped, &session)?;
+            Ok((session, vec![]))
+        });
+        assert!(matches!(result, Err(Error::Conflict(_))));
+    }
+
+    #[test]
+    fn transition_gives_up() {
+        let store = conflict_store(Session::default(), SAVE_ATTEMPTS);
+        let session_store = SessionStore::new(&store);
+
+        let result = session_store.transition(Session::default(), &|session| Ok((session, vec![])));
+        assert!(matches!(result, Err(Error::Conflict(_))));
+    }
+
     #[test]
     fn load_invalid_data() {
         let store = MockStore {
@@ -143,6 +351,84 @@ mod tests {
             Err(Error::NewerVersion(version)) => assert_eq!(version, "v99"),
             other => panic!("Should fail but got {:?}", other),
         }
+        // Drivers changed format, not even read-only
+        assert!(session_store.load_read_only().is_err());
+    }
+
+    #[test]
+    fn newer_version_read_only() {
+        let json = r#"{
+                  "version": "v99",
+                  "drivers": [{ "name": "alice", "pronouns": "she/her" }],
+                  "branches": {
+                    "branch": "mob-session",
+                    "base_branch": "main"
+                  },
+                  "settings": null,
+                  "state": "Stopped",
+                  "last_break": null,
+                  "mob_time": 0,
+                  "teleporter": "enabled"
+                }"#;
+        let store = MockStore {
+            load_data: Vec::from(json),
+        };
+
+        let session_store = SessionStore::new(&store);
+        assert!(matches!(
+            session_store.load(),
+            Err(Error::NewerVersion(version)) if version == "v99"
+        ));
+
+        let session = session_store.load_read_only().unwrap();
+        assert_eq!(session.newer_version, Some("v99".to_string()));
+        assert_eq!(session.drivers.all(), vec!["alice"]);
+        assert!(matches!(
+            session_store.save(session),
+            Err(Error::NewerVersion(version)) if version == "v99"
+        ));
+    }
+
+    #[test]
+    fn v1_working() {
+        let json = r#"{
+                  "version": "v1",
+                  "drivers": ["alice", "bob"],
+                  "branches": {
+                    "branch": "mob-session",
+                    "base_branch": "main"
+                  },
+                  "settings": {
+                    "commit_message": "mob sync [skip ci]",
+                    "work_duration": 15
+                  },
+                  "state": {
+                    "Working": {
+                      "driver": "alice"
+                    }
+                  }
+                }"#;
+        let store = MockStore {
+            load_data: Vec::from(json),
+        };
+
+        let session_store = SessionStore::new(&store);
+
+        match session_store.load() {
+            Ok(Session {
+                state:
+                    State::Working {
+                        driver,
+                        work_duration,
+                        ..
+                    },
+                ..
+            }) => {
+                assert_eq!(driver, "alice");
+                assert_eq!(work_duration, 15);
+            }
+            other => panic!("Expected working session but got {:?}", other),
+        }
     }
 
     #[test]
@@ -179,4 +465,71 @@ mod tests {
             panic!("Got error but expected oldest version: {:?}", err)
         }
     }
+
+    #[test]
+    fn unversioned_break() {
+        let json = r#"{
+                  "drivers": [],
+                  "last_break": "2021-09-10T14:22:41.083716Z",
+                  "branches": {
+                    "branch": "mob-session",
+                    "base_branch": "main"
+                  },
+                  "settings": {
+                    "commit_message": "mob sync [skip ci]",
+                    "work_duration": 10,
+                    "break_duration": 5,
+                    "break_interval": 55,
+                    "lunch_start": "11:30",
+                    "lunch_end": "12:30"
+                  },
+                  "state": {
+                    "WaitingForNext": {
+                      "next": "Johan Rydenstam",
+                      "is_break": true
+                    }
+                  }
+                }"#;
+        let store = MockStore {
+            load_data: Vec::from(json),
+        };
+
+        let session = SessionStore::new(&store).load().unwrap();
+        let settings = session.settings.unwrap();
+
+        assert_eq!(settings.break_interval, 55);
+        assert_eq!(settings.lunch_start, Some("11:30".to_string()));
+        assert!(session.last_break.is_some());
+        match session.state {
+            State::Break { next, until } => {
+                assert_eq!(next, Some("Johan Rydenstam".to_string()));
+                assert_eq!(until.to_rfc3339(), "2021-09-10T14:27:41.083716+00:00");
+            }
+            other => panic!("Expected break but got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn v3_drivers() {
+        let json = r#"{
+                  "version": "v3",
+                  "drivers": ["alice", "bob"],
+                  "branches": {
+                    "branch": "mob-session",
+                    "base_branch": "main"
+                  },
+                  "settings": null,
+                  "state": "Stopped",
+                  "last_break": null,
+                  "mob_time": 0
+                }"#;
+        let store = MockStore {
+            load_data: Vec::from(json),
+        };
+
+        let session = SessionStore::new(&store).load().unwrap();
+
+        assert_eq!(session.drivers.all(), vec!["alice", "bob"]);
+        assert!(!session.drivers.is_away("alice"));
+    }
 }
@@ -5,8 +5,8 @@ use serde::{Deserialize, Serialize};
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SessionV0 {
     pub last_break: DateTime<Utc>,
-    pub drivers: Drivers,
-    pub branches: Branches,
+    pub drivers: DriversV0,
+    pub branches: BranchesV0,
     pub settings: Option<SettingsV0>,
     pub state: StateV0,
 }
@@ -0,0 +1,49 @@
+use super::settings_v1::Extra;
+use super::v0::{SessionV0, StateV0};
+use super::v1::*;
+use chrono::Duration;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionV1 {
+    pub drivers: DriversV0,
+    pub branches: BranchesV0,
+    pub settings: Option<SettingsV1>,
+    pub state: StateV1,
+    /// Fields v1 doesn't know, the breaks of v0 are kept here for v3
+    #[serde(flatten)]
+    pub extra: Extra,
+}
+
+impl From<SessionV0> for SessionV1 {
+    fn from(session_v0: SessionV0) -> Self {
+        let last_break = session_v0.last_break;
+        let mut extra = Extra::new();
+        extra.insert("last_break".to_string(), serde_json::json!(last_break));
+
+        let state = match session_v0.state {
+            StateV0::Stopped => StateV1::Stopped,
+            StateV0::Working { driver } => StateV1::Working { driver },
+            StateV0::WaitingForNext { next, is_break } => {
+                if is_break {
+                    let break_duration = session_v0
+                        .settings
+                        .as_ref()
+                        .map(|settings| settings.break_duration)
+                        .unwrap_or_default();
+                    let until = last_break + Duration::minutes(break_duration);
+                    extra.insert("break_until".to_string(), serde_json::json!(until));
+                }
+                StateV1::WaitingForNext { next }
+            }
+        };
+
+        Self {
+            drivers: session_v0.drivers,
+            branches: session_v0.branches,
+            settings: session_v0.settings.map(SettingsV1::from),
+            state,
+            extra,
+        }
+    }
+}
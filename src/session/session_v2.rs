@@ -0,0 +1,43 @@
+use super::settings_v1::Extra;
+use super::v1::{SessionV1, StateV1};
+use super::v2::*;
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionV2 {
+    pub drivers: DriversV0,
+    pub branches: BranchesV0,
+    pub settings: Option<SettingsV1>,
+    pub state: StateV2,
+    /// Fields v2 doesn't know, the breaks of v0 are kept here for v3
+    #[serde(flatten)]
+    pub extra: Extra,
+}
+
+impl From<SessionV1> for SessionV2 {
+    fn from(session_v1: SessionV1) -> Self {
+        let work_duration = session_v1
+            .settings
+            .as_ref()
+            .map(|settings| settings.work_duration)
+            .unwrap_or_else(|| SettingsV1::default().work_duration);
+
+        Self {
+            drivers: session_v1.drivers,
+            branches: session_v1.branches,
+            settings: session_v1.settings,
+            state: match session_v1.state {
+                StateV1::Stopped => StateV2::Stopped,
+                // The start of the turn was never recorded, count from now
+                StateV1::Working { driver } => StateV2::Working {
+                    driver,
+                    started: Utc::now(),
+                    work_duration,
+                },
+                StateV1::WaitingForNext { next } => StateV2::WaitingForNext { next },
+            },
+            extra: session_v1.extra,
+        }
+    }
+}
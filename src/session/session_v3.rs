@@ -0,0 +1,49 @@
+use super::settings_v1::extra_field;
+use super::v2::{SessionV2, StateV2};
+use super::v3::*;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+type DurationMinutes = i64;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionV3 {
+    pub drivers: DriversV0,
+    pub branches: BranchesV0,
+    pub settings: Option<SettingsV3>,
+    pub state: StateV3,
+    pub last_break: Option<DateTime<Utc>>,
+    pub mob_time: DurationMinutes,
+    #[serde(default)]
+    pub navigator: Option<String>,
+}
+
+impl From<SessionV2> for SessionV3 {
+    fn from(session_v2: SessionV2) -> Self {
+        let break_until: Option<DateTime<Utc>> = extra_field(&session_v2.extra, "break_until");
+        Self {
+            drivers: session_v2.drivers,
+            branches: session_v2.branches,
+            settings: session_v2.settings.map(SettingsV3::from),
+            state: match session_v2.state {
+                StateV2::Stopped => StateV3::Stopped,
+                StateV2::Working {
+                    driver,
+                    started,
+                    work_duration,
+                } => StateV3::Working {
+                    driver,
+                    started,
+                    work_duration,
+                },
+                StateV2::WaitingForNext { next } => match break_until {
+                    Some(until) => StateV3::Break { next, until },
+                    None => StateV3::WaitingForNext { next },
+                },
+            },
+            last_break: extra_field(&session_v2.extra, "last_break"),
+            mob_time: 0,
+            navigator: None,
+        }
+    }
+}
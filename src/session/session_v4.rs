@@ -0,0 +1,32 @@
+use super::v3::SessionV3;
+use super::v4::*;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+type DurationMinutes = i64;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionV4 {
+    pub drivers: DriversV4,
+    pub branches: BranchesV0,
+    pub settings: Option<SettingsV3>,
+    pub state: StateV3,
+    pub last_break: Option<DateTime<Utc>>,
+    pub mob_time: DurationMinutes,
+    #[serde(default)]
+    pub navigator: Option<String>,
+}
+
+impl From<SessionV3> for SessionV4 {
+    fn from(session_v3: SessionV3) -> Self {
+        Self {
+            drivers: session_v3.drivers.into(),
+            branches: session_v3.branches,
+            settings: session_v3.settings,
+            state: session_v3.state,
+            last_break: session_v3.last_break,
+            mob_time: session_v3.mob_time,
+            navigator: session_v3.navigator,
+        }
+    }
+}
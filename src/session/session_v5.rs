@@ -0,0 +1,37 @@
+use super::settings_v1::Extra;
+use super::v4::SessionV4;
+use super::v5::*;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+type DurationMinutes = i64;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionV5 {
+    pub drivers: DriversV4,
+    pub branches: BranchesV0,
+    pub settings: Option<SettingsV3>,
+    pub state: StateV5,
+    pub last_break: Option<DateTime<Utc>>,
+    pub mob_time: DurationMinutes,
+    #[serde(default)]
+    pub navigator: Option<String>,
+    /// Fields written by newer clients
+    #[serde(flatten)]
+    pub extra: Extra,
+}
+
+impl From<SessionV4> for SessionV5 {
+    fn from(session_v4: SessionV4) -> Self {
+        Self {
+            drivers: session_v4.drivers,
+            branches: session_v4.branches,
+            settings: session_v4.settings,
+            state: session_v4.state.into(),
+            last_break: session_v4.last_break,
+            mob_time: session_v4.mob_time,
+            navigator: session_v4.navigator,
+            extra: Extra::new(),
+        }
+    }
+}
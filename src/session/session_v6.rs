@@ -0,0 +1,36 @@
+use super::settings_v1::Extra;
+use super::v5::SessionV5;
+use super::v6::*;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+type DurationMinutes = i64;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionV6 {
+    pub drivers: DriversV6,
+    pub branches: BranchesV0,
+    pub settings: Option<SettingsV3>,
+    pub state: StateV5,
+    pub last_break: Option<DateTime<Utc>>,
+    pub mob_time: DurationMinutes,
+    #[serde(default)]
+    pub navigator: Option<String>,
+    #[serde(flatten)]
+    pub extra: Extra,
+}
+
+impl From<SessionV5> for SessionV6 {
+    fn from(session_v5: SessionV5) -> Self {
+        Self {
+            drivers: session_v5.drivers.into(),
+            branches: session_v5.branches,
+            settings: session_v5.settings,
+            state: session_v5.state,
+            last_break: session_v5.last_break,
+            mob_time: session_v5.mob_time,
+            navigator: session_v5.navigator,
+            extra: session_v5.extra,
+        }
+    }
+}
@@ -1,13 +1,96 @@
+use super::rotation::Rotation;
 use crate::prompt::Prompter;
 use anyhow::Result;
+use chrono::NaiveTime;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 
 type DurationMinutes = i64;
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+/// Who navigates while someone drives
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+pub enum Navigator {
+    /// The driver after the current one, so the next driver is up to speed
+    #[default]
+    NextDriver,
+    /// The driver before the current one
+    PreviousDriver,
+    Nobody,
+}
+
+impl Navigator {
+    pub const ALL: [Navigator; 3] = [
+        Navigator::NextDriver,
+        Navigator::PreviousDriver,
+        Navigator::Nobody,
+    ];
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            Navigator::NextDriver => "Next driver",
+            Navigator::PreviousDriver => "Previous driver",
+            Navigator::Nobody => "Nobody",
+        }
+    }
+}
+
+/// How mob done brings the mob branch into the base branch
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+pub enum DoneStrategy {
+    /// Squash onto the base branch and leave the changes staged
+    #[default]
+    Squash,
+    /// Squash onto the base branch and commit with a generated message
+    SquashCommit,
+    /// Merge the mob branch into the base branch with a merge commit
+    Merge,
+    /// Rebase the commits of every turn onto the base branch
+    Rebase,
+    /// Rebase and fold fixup! and squash! commits into their targets
+    RebaseAutosquash,
+    /// Squash onto a new branch from the base branch and push it for review
+    Review,
+}
+
+impl DoneStrategy {
+    pub const ALL: [DoneStrategy; 6] = [
+        DoneStrategy::Squash,
+        DoneStrategy::SquashCommit,
+        DoneStrategy::Merge,
+        DoneStrategy::Rebase,
+        DoneStrategy::RebaseAutosquash,
+        DoneStrategy::Review,
+    ];
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            DoneStrategy::Squash => "Squash and leave staged",
+            DoneStrategy::SquashCommit => "Squash and commit",
+            DoneStrategy::Merge => "Merge commit",
+            DoneStrategy::Rebase => "Rebase",
+            DoneStrategy::RebaseAutosquash => "Rebase with autosquash",
+            DoneStrategy::Review => "Squash onto a review branch",
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
 pub struct Settings {
     pub commit_message: String,
     pub work_duration: DurationMinutes,
+    /// Mob time between breaks, 0 disables breaks
+    pub break_interval: DurationMinutes,
+    pub break_duration: DurationMinutes,
+    /// Local time as HH:MM
+    pub lunch_start: Option<String>,
+    pub lunch_end: Option<String>,
+    pub navigator: Navigator,
+    pub rotation: Rotation,
+    pub done_strategy: DoneStrategy,
+    /// Settings only newer versions offer, `mob settings` leaves them as they are
+    #[serde(flatten)]
+    pub extra: BTreeMap<String, serde_json::Value>,
 }
 
 impl Default for Settings {
@@ -15,22 +98,110 @@ impl Default for Settings {
         Self {
             commit_message: "mob sync [skip ci]".into(),
             work_duration: 10,
+            break_interval: 55,
+            break_duration: 5,
+            lunch_start: None,
+            lunch_end: None,
+            navigator: Navigator::default(),
+            rotation: Rotation::default(),
+            done_strategy: DoneStrategy::default(),
+            extra: BTreeMap::new(),
         }
     }
 }
 
 impl Settings {
     pub fn ask(prompter: &dyn Prompter) -> Result<Self> {
-        let default = Settings::default();
+        Settings::default().edit(prompter)
+    }
+
+    /// Asks for new settings with the current ones as defaults
+    pub fn edit(self, prompter: &dyn Prompter) -> Result<Self> {
+        let default = self;
 
         let commit_message = prompter.input_string("Commit message", &default.commit_message)?;
 
         let work_duration = prompter.input_i64("Work duration", default.work_duration)?;
 
+        let break_interval = prompter.input_i64(
+            "Minutes between breaks (0 for no breaks)",
+            default.break_interval,
+        )?;
+
+        let break_duration = if break_interval > 0 {
+            prompter.input_i64("Break duration", default.break_duration)?
+        } else {
+            default.break_duration
+        };
+
+        let (lunch_start, lunch_end) =
+            if prompter.confirm("Stop for lunch?", default.lunch_start.is_some())? {
+                let lunch_start = prompter.input_string(
+                    "Lunch starts at (HH:MM)",
+                    default.lunch_start.as_deref().unwrap_or("12:00"),
+                )?;
+                let lunch_end = prompter.input_string(
+                    "Lunch ends at (HH:MM)",
+                    default.lunch_end.as_deref().unwrap_or("13:00"),
+                )?;
+                (Some(lunch_start), Some(lunch_end))
+            } else {
+                (None, None)
+            };
+
+        let descriptions: Vec<&str> = Navigator::ALL.iter().map(|n| n.description()).collect();
+        let navigator = prompter.select_with_prompt(
+            "Who should navigate",
+            &descriptions,
+            Navigator::ALL
+                .iter()
+                .position(|navigator| *navigator == default.navigator)
+                .unwrap_or_default(),
+        )?;
+
+        let descriptions: Vec<&str> = Rotation::ALL.iter().map(|r| r.description()).collect();
+        let rotation = prompter.select_with_prompt(
+            "How to pick the next driver",
+            &descriptions,
+            Rotation::ALL
+                .iter()
+                .position(|rotation| *rotation == default.rotation)
+                .unwrap_or_default(),
+        )?;
+
+        let descriptions: Vec<&str> = DoneStrategy::ALL.iter().map(|s| s.description()).collect();
+        let done_strategy = prompter.select_with_prompt(
+            "How mob done should finish",
+            &descriptions,
+            DoneStrategy::ALL
+                .iter()
+                .position(|strategy| *strategy == default.done_strategy)
+                .unwrap_or_default(),
+        )?;
+
         let config = Self {
             commit_message,
             work_duration,
+            break_interval,
+            break_duration,
+            lunch_start,
+            lunch_end,
+            navigator: Navigator::ALL[navigator],
+            rotation: Rotation::ALL[rotation],
+            done_strategy: DoneStrategy::ALL[done_strategy],
+            ..default
         };
         Ok(config)
     }
+
+    /// Start and end of lunch if both are set and valid
+    pub fn lunch(&self) -> Option<(NaiveTime, NaiveTime)> {
+        let parse = |time: &Option<String>| -> Option<NaiveTime> {
+            let time = time.as_ref()?;
+            NaiveTime::parse_from_str(time, "%H:%M")
+                .map_err(|_| log::warn!("Invalid lunch time '{}', use HH:MM", time))
+                .ok()
+        };
+        Some((parse(&self.lunch_start)?, parse(&self.lunch_end)?))
+    }
 }
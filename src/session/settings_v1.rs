@@ -0,0 +1,52 @@
+use super::v0::SettingsV0;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+type DurationMinutes = i64;
+
+/// Fields an old version doesn't know, kept for a later version that does
+pub type Extra = BTreeMap<String, serde_json::Value>;
+
+/// Field `name` of `extra`, None if missing or of another type
+pub fn extra_field<T: DeserializeOwned>(extra: &Extra, name: &str) -> Option<T> {
+    serde_json::from_value(extra.get(name)?.clone()).ok()
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SettingsV1 {
+    pub commit_message: String,
+    pub work_duration: DurationMinutes,
+    /// Fields v1 doesn't know, the breaks of v0 are kept here for v3
+    #[serde(flatten)]
+    pub extra: Extra,
+}
+
+impl Default for SettingsV1 {
+    fn default() -> Self {
+        Self {
+            commit_message: "mob sync [skip ci]".into(),
+            work_duration: 10,
+            extra: Extra::new(),
+        }
+    }
+}
+
+impl From<SettingsV0> for SettingsV1 {
+    fn from(settings_v0: SettingsV0) -> Self {
+        let extra = [
+            ("break_duration", settings_v0.break_duration.into()),
+            ("break_interval", settings_v0.break_interval.into()),
+            ("lunch_start", settings_v0.lunch_start.into()),
+            ("lunch_end", settings_v0.lunch_end.into()),
+        ];
+        Self {
+            commit_message: settings_v0.commit_message,
+            work_duration: settings_v0.work_duration,
+            extra: extra
+                .into_iter()
+                .map(|(name, value)| (name.to_string(), value))
+                .collect(),
+        }
+    }
+}
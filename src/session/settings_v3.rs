@@ -0,0 +1,92 @@
+use super::rotation::Rotation;
+use super::settings::{Navigator, Settings};
+use super::settings_v1::extra_field;
+use super::v1::SettingsV1;
+use serde::{Deserialize, Serialize};
+
+type DurationMinutes = i64;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default)]
+pub enum NavigatorV3 {
+    #[default]
+    NextDriver,
+    PreviousDriver,
+    Nobody,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default)]
+pub enum RotationV3 {
+    #[default]
+    RoundRobin,
+    Random,
+    LeastRecent,
+    Pick,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SettingsV3 {
+    pub commit_message: String,
+    pub work_duration: DurationMinutes,
+    pub break_interval: DurationMinutes,
+    pub break_duration: DurationMinutes,
+    pub lunch_start: Option<String>,
+    pub lunch_end: Option<String>,
+    #[serde(default)]
+    pub navigator: NavigatorV3,
+    #[serde(default)]
+    pub rotation: RotationV3,
+}
+
+impl From<SettingsV1> for SettingsV3 {
+    fn from(settings_v1: SettingsV1) -> Self {
+        let extra = &settings_v1.extra;
+        Self {
+            // Breaks were only set in v0, v1 and v2 sessions had none
+            break_interval: extra_field(extra, "break_interval").unwrap_or(0),
+            break_duration: extra_field(extra, "break_duration").unwrap_or(5),
+            lunch_start: extra_field(extra, "lunch_start"),
+            lunch_end: extra_field(extra, "lunch_end"),
+            commit_message: settings_v1.commit_message,
+            work_duration: settings_v1.work_duration,
+            navigator: NavigatorV3::default(),
+            rotation: RotationV3::default(),
+        }
+    }
+}
+
+impl From<SettingsV3> for Settings {
+    fn from(settings_v3: SettingsV3) -> Self {
+        Settings {
+            commit_message: settings_v3.commit_message,
+            work_duration: settings_v3.work_duration,
+            break_interval: settings_v3.break_interval,
+            break_duration: settings_v3.break_duration,
+            lunch_start: settings_v3.lunch_start,
+            lunch_end: settings_v3.lunch_end,
+            navigator: settings_v3.navigator.into(),
+            rotation: settings_v3.rotation.into(),
+            ..Settings::default()
+        }
+    }
+}
+
+impl From<NavigatorV3> for Navigator {
+    fn from(navigator_v3: NavigatorV3) -> Self {
+        match navigator_v3 {
+            NavigatorV3::NextDriver => Navigator::NextDriver,
+            NavigatorV3::PreviousDriver => Navigator::PreviousDriver,
+            NavigatorV3::Nobody => Navigator::Nobody,
+        }
+    }
+}
+
+impl From<RotationV3> for Rotation {
+    fn from(rotation_v3: RotationV3) -> Self {
+        match rotation_v3 {
+            RotationV3::RoundRobin => Rotation::RoundRobin,
+            RotationV3::Random => Rotation::Random,
+            RotationV3::LeastRecent => Rotation::LeastRecent,
+            RotationV3::Pick => Rotation::Pick,
+        }
+    }
+}
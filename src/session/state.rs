@@ -1,8 +1,388 @@
+use chrono::{DateTime, Duration, Local, Utc};
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+type DurationMinutes = i64;
+
+/// How often a running timer refreshes the driver's lease, each
+/// heartbeat fetches and pushes the session
+pub const HEARTBEAT_INTERVAL: i64 = 5;
+/// Minutes without a heartbeat after the end of the turn before it is
+/// considered abandoned
+pub const LEASE_DURATION: i64 = 15;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum State {
     Stopped,
-    Working { driver: String },
-    WaitingForNext { next: Option<String> },
+    Working {
+        driver: String,
+        started: DateTime<Utc>,
+        work_duration: DurationMinutes,
+        /// Last heartbeat from the driver's timer
+        #[serde(default)]
+        last_seen: Option<DateTime<Utc>>,
+    },
+    WaitingForNext {
+        next: Option<String>,
+    },
+    Break {
+        next: Option<String>,
+        until: DateTime<Utc>,
+    },
+    Paused {
+        reason: Option<String>,
+        since: DateTime<Utc>,
+        /// When the mob expects to resume
+        until: Option<DateTime<Utc>>,
+        /// State to return to when resuming
+        previous: Box<State>,
+    },
+}
+
+impl State {
+    /// Time left of the current turn, negative if the driver is overdue
+    pub fn time_left(&self, now: DateTime<Utc>) -> Option<Duration> {
+        match self {
+            State::Working {
+                started,
+                work_duration,
+                ..
+            } => Some(*started + Duration::minutes(*work_duration) - now),
+            _ => None,
+        }
+    }
+
+    /// Turns a break that has ended into waiting for the next driver
+    pub fn end_break(self, now: DateTime<Utc>) -> Self {
+        match self {
+            State::Break { next, until } if until <= now => State::WaitingForNext { next },
+            state => state,
+        }
+    }
+
+    /// Same state apart from heartbeats
+    pub fn same_as(&self, other: &State) -> bool {
+        fn unseen(state: &State) -> State {
+            match state.clone() {
+                State::Working {
+                    driver,
+                    started,
+                    work_duration,
+                    ..
+                } => State::Working {
+                    driver,
+                    started,
+                    work_duration,
+                    last_seen: None,
+                },
+                state => state,
+            }
+        }
+        unseen(self) == unseen(other)
+    }
+
+    /// How long ago the driver was last seen, None if unknown
+    pub fn last_seen(&self, now: DateTime<Utc>) -> Option<Duration> {
+        match self {
+            State::Working {
+                last_seen: Some(last_seen),
+                ..
+            } => Some(now - *last_seen),
+            _ => None,
+        }
+    }
+
+    /// True if the driver has not been seen for longer than the lease,
+    /// counted from the end of the turn since the timer stops there
+    pub fn lease_expired(&self, now: DateTime<Utc>) -> bool {
+        match self {
+            State::Working {
+                started,
+                work_duration,
+                last_seen: Some(last_seen),
+                ..
+            } => {
+                let turn_end = *started + Duration::minutes(*work_duration);
+                now - (*last_seen).max(turn_end) > Duration::minutes(LEASE_DURATION)
+            }
+            _ => false,
+        }
+    }
+
+    /// Refreshes the lease if `me` is driving
+    pub fn heartbeat(self, me: &str, now: DateTime<Utc>) -> Self {
+        match self {
+            State::Working {
+                driver,
+                started,
+                work_duration,
+                ..
+            } if driver == me => State::Working {
+                driver,
+                started,
+                work_duration,
+                last_seen: Some(now),
+            },
+            state => state,
+        }
+    }
+
+    /// The state the mob is in, or will return to if paused
+    pub fn active(&self) -> &State {
+        match self {
+            State::Paused { previous, .. } => previous.active(),
+            state => state,
+        }
+    }
+
+    /// Why and until when the mob is paused, None if not paused
+    pub fn pause_description(&self) -> Option<String> {
+        match self {
+            State::Paused { reason, until, .. } => {
+                let mut description = String::from("paused");
+                if let Some(reason) = reason {
+                    description.push_str(&format!(" for {}", reason));
+                }
+                if let Some(until) = until {
+                    description.push_str(&format!(
+                        " until {}",
+                        until.with_timezone(&Local).format("%H:%M")
+                    ));
+                }
+                Some(description)
+            }
+            _ => None,
+        }
+    }
+
+    pub fn pause(self, reason: Option<String>, until: Option<DateTime<Utc>>) -> Self {
+        State::Paused {
+            reason,
+            since: Utc::now(),
+            until,
+            previous: Box::new(self),
+        }
+    }
+
+    /// Returns to the state before the pause, the time paused does not
+    /// count towards the turn
+    pub fn resume(self, now: DateTime<Utc>) -> Self {
+        match self {
+            State::Paused {
+                since, previous, ..
+            } => match *previous {
+                State::Working {
+                    driver,
+                    started,
+                    work_duration,
+                    ..
+                } => State::Working {
+                    driver,
+                    started: started + (now - since),
+                    work_duration,
+                    last_seen: Some(now),
+                },
+                state => state,
+            },
+            state => state,
+        }
+    }
+
+    /// Renames the driver `from` to `to` wherever it appears
+    pub fn rename(self, from: &str, to: &str) -> Self {
+        let rename = |name: String| if name == from { to.to_string() } else { name };
+        match self {
+            State::Working {
+                driver,
+                started,
+                work_duration,
+                last_seen,
+            } => State::Working {
+                driver: rename(driver),
+                started,
+                work_duration,
+                last_seen,
+            },
+            State::WaitingForNext { next } => State::WaitingForNext {
+                next: next.map(rename),
+            },
+            State::Break { next, until } => State::Break {
+                next: next.map(rename),
+                until,
+            },
+            State::Paused {
+                reason,
+                since,
+                until,
+                previous,
+            } => State::Paused {
+                reason,
+                since,
+                until,
+                previous: Box::new(previous.rename(from, to)),
+            },
+            State::Stopped => State::Stopped,
+        }
+    }
+
+    /// Replaces `name` as the next driver
+    pub fn replace_next(self, name: &str, replacement: Option<String>) -> Self {
+        match self {
+            State::WaitingForNext { next: Some(next) } if next == name => {
+                State::WaitingForNext { next: replacement }
+            }
+            State::Break {
+                next: Some(next),
+                until,
+            } if next == name => State::Break {
+                next: replacement,
+                until,
+            },
+            State::Paused {
+                reason,
+                since,
+                until,
+                previous,
+            } => State::Paused {
+                reason,
+                since,
+                until,
+                previous: Box::new(previous.replace_next(name, replacement)),
+            },
+            state => state,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn time_left_working() {
+        let now = Utc::now();
+        let state = State::Working {
+            driver: "one".to_string(),
+            started: now - Duration::minutes(4),
+            work_duration: 10,
+            last_seen: None,
+        };
+
+        assert_eq!(state.time_left(now), Some(Duration::minutes(6)));
+    }
+
+    #[test]
+    fn time_left_overdue() {
+        let now = Utc::now();
+        let state = State::Working {
+            driver: "one".to_string(),
+            started: now - Duration::minutes(12),
+            work_duration: 10,
+            last_seen: None,
+        };
+
+        assert_eq!(state.time_left(now), Some(Duration::minutes(-2)));
+    }
+
+    #[test]
+    fn time_left_waiting() {
+        let state = State::WaitingForNext { next: None };
+
+        assert_eq!(state.time_left(Utc::now()), None);
+    }
+
+    #[test]
+    fn end_break_ongoing() {
+        let now = Utc::now();
+        let state = State::Break {
+            next: None,
+            until: now + Duration::minutes(1),
+        };
+
+        assert!(matches!(state.end_break(now), State::Break { .. }));
+    }
+
+    #[test]
+    fn end_break_ended() {
+        let now = Utc::now();
+        let state = State::Break {
+            next: Some("one".to_string()),
+            until: now,
+        };
+
+        assert!(matches!(
+            state.end_break(now),
+            State::WaitingForNext { next: Some(next) } if next == "one"
+        ));
+    }
+
+    #[test]
+    fn resume_working() {
+        let now = Utc::now();
+        let started = now - Duration::minutes(30);
+        let state = State::Paused {
+            reason: None,
+            since: now - Duration::minutes(20),
+            until: None,
+            previous: Box::new(State::Working {
+                driver: "one".to_string(),
+                started,
+                work_duration: 15,
+                last_seen: None,
+            }),
+        };
+
+        let resumed = state.resume(now);
+        assert_eq!(resumed.time_left(now), Some(Duration::minutes(5)));
+    }
+
+    #[test]
+    fn replace_next_paused() {
+        let state = State::WaitingForNext {
+            next: Some("one".to_string()),
+        }
+        .pause(Some("lunch".to_string()), None)
+        .replace_next("one", Some("two".to_string()));
+
+        assert!(matches!(state, State::Paused { .. }));
+        assert!(matches!(
+            state.active(),
+            State::WaitingForNext { next: Some(next) } if next == "two"
+        ));
+    }
+
+    #[test]
+    fn lease() {
+        let now = Utc::now();
+        let state = State::Working {
+            driver: "one".to_string(),
+            started: now - Duration::minutes(60),
+            work_duration: 15,
+            last_seen: None,
+        };
+        assert!(!state.lease_expired(now));
+
+        let state = state.heartbeat("one", now - Duration::minutes(LEASE_DURATION + 1));
+        assert!(state.lease_expired(now));
+
+        let state = state.heartbeat("two", now);
+        assert!(state.lease_expired(now));
+
+        let state = state.heartbeat("one", now);
+        assert!(!state.lease_expired(now));
+        assert_eq!(state.last_seen(now), Some(Duration::zero()));
+    }
+
+    #[test]
+    fn lease_from_end_of_turn() {
+        let now = Utc::now();
+        // The timer stopped heartbeats when the turn ended
+        let state = State::Working {
+            driver: "one".to_string(),
+            started: now - Duration::minutes(LEASE_DURATION + 10),
+            work_duration: 10,
+            last_seen: Some(now - Duration::minutes(LEASE_DURATION + 1)),
+        };
+        assert!(!state.lease_expired(now));
+        assert!(state.lease_expired(now + Duration::minutes(1)));
+    }
 }
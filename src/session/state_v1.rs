@@ -0,0 +1,8 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum StateV1 {
+    Stopped,
+    Working { driver: String },
+    WaitingForNext { next: Option<String> },
+}
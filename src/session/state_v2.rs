@@ -0,0 +1,17 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+type DurationMinutes = i64;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum StateV2 {
+    Stopped,
+    Working {
+        driver: String,
+        started: DateTime<Utc>,
+        work_duration: DurationMinutes,
+    },
+    WaitingForNext {
+        next: Option<String>,
+    },
+}
@@ -0,0 +1,21 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+type DurationMinutes = i64;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum StateV3 {
+    Stopped,
+    Working {
+        driver: String,
+        started: DateTime<Utc>,
+        work_duration: DurationMinutes,
+    },
+    WaitingForNext {
+        next: Option<String>,
+    },
+    Break {
+        next: Option<String>,
+        until: DateTime<Utc>,
+    },
+}
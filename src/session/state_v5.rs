@@ -0,0 +1,83 @@
+use super::state::State;
+use super::v4::StateV3;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+type DurationMinutes = i64;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum StateV5 {
+    Stopped,
+    Working {
+        driver: String,
+        started: DateTime<Utc>,
+        work_duration: DurationMinutes,
+        #[serde(default)]
+        last_seen: Option<DateTime<Utc>>,
+    },
+    WaitingForNext {
+        next: Option<String>,
+    },
+    Break {
+        next: Option<String>,
+        until: DateTime<Utc>,
+    },
+    Paused {
+        reason: Option<String>,
+        since: DateTime<Utc>,
+        until: Option<DateTime<Utc>>,
+        previous: Box<StateV5>,
+    },
+}
+
+impl From<StateV3> for StateV5 {
+    fn from(state_v3: StateV3) -> Self {
+        match state_v3 {
+            StateV3::Stopped => StateV5::Stopped,
+            StateV3::Working {
+                driver,
+                started,
+                work_duration,
+            } => StateV5::Working {
+                driver,
+                started,
+                work_duration,
+                last_seen: None,
+            },
+            StateV3::WaitingForNext { next } => StateV5::WaitingForNext { next },
+            StateV3::Break { next, until } => StateV5::Break { next, until },
+        }
+    }
+}
+
+impl From<StateV5> for State {
+    fn from(state_v5: StateV5) -> Self {
+        match state_v5 {
+            StateV5::Stopped => State::Stopped,
+            StateV5::Working {
+                driver,
+                started,
+                work_duration,
+                last_seen,
+            } => State::Working {
+                driver,
+                started,
+                work_duration,
+                last_seen,
+            },
+            StateV5::WaitingForNext { next } => State::WaitingForNext { next },
+            StateV5::Break { next, until } => State::Break { next, until },
+            StateV5::Paused {
+                reason,
+                since,
+                until,
+                previous,
+            } => State::Paused {
+                reason,
+                since,
+                until,
+                previous: Box::new(State::from(*previous)),
+            },
+        }
+    }
+}
@@ -1,13 +1,40 @@
 use super::session::Session;
 use super::session_v0::SessionV0;
+use super::session_v1::SessionV1;
+use super::session_v2::SessionV2;
+use super::session_v3::SessionV3;
+use super::session_v4::SessionV4;
+use super::session_v5::SessionV5;
+use super::session_v6::SessionV6;
 use serde::{Deserialize, Serialize};
 
+/// Version written by this client
+pub const LATEST_VERSION: u32 = 7;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 // Minimal struct to get version
 pub struct Versioned {
     pub version: Option<String>,
 }
 
+impl Versioned {
+    /// `v3` is 3, None if not a version we know the format of
+    pub fn number(version: &str) -> Option<u32> {
+        version
+            .strip_prefix('v')
+            .and_then(|number| number.parse().ok())
+    }
+
+    pub fn is_known(version: &str) -> bool {
+        Versioned::number(version).is_some_and(|number| number <= LATEST_VERSION)
+    }
+}
+
+/// A stored session, one variant per schema version.
+///
+/// To change the schema, freeze the current types as `*VN` snapshots, add
+/// a variant holding the snapshot for the current version with a migration
+/// step in `migrate`, then bump `LATEST_VERSION` and the tag of `Latest`.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "version")]
 pub enum VersionedSession {
@@ -15,24 +42,208 @@ pub enum VersionedSession {
     V0(SessionV0),
 
     #[serde(rename = "v1")]
-    V1(Session),
+    V1(SessionV1),
+
+    #[serde(rename = "v2")]
+    V2(SessionV2),
+
+    #[serde(rename = "v3")]
+    V3(SessionV3),
+
+    #[serde(rename = "v4")]
+    V4(SessionV4),
+
+    #[serde(rename = "v5")]
+    V5(SessionV5),
+
+    #[serde(rename = "v6")]
+    V6(SessionV6),
+
+    /// The version this client writes, tagged `v{LATEST_VERSION}`
+    #[serde(rename = "v7")]
+    Latest(Session),
 }
 
 impl VersionedSession {
+    pub fn number(&self) -> u32 {
+        match self {
+            VersionedSession::V0(_) => 0,
+            VersionedSession::V1(_) => 1,
+            VersionedSession::V2(_) => 2,
+            VersionedSession::V3(_) => 3,
+            VersionedSession::V4(_) => 4,
+            VersionedSession::V5(_) => 5,
+            VersionedSession::V6(_) => 6,
+            VersionedSession::Latest(_) => LATEST_VERSION,
+        }
+    }
+
+    /// One migration step towards the latest version
     fn migrate(self) -> Self {
         match self {
-            VersionedSession::V0(session) => VersionedSession::V1(Session::from(session)),
-            VersionedSession::V1(_) => self,
+            VersionedSession::V0(session) => VersionedSession::V1(SessionV1::from(session)),
+            VersionedSession::V1(session) => VersionedSession::V2(SessionV2::from(session)),
+            VersionedSession::V2(session) => VersionedSession::V3(SessionV3::from(session)),
+            VersionedSession::V3(session) => VersionedSession::V4(SessionV4::from(session)),
+            VersionedSession::V4(session) => VersionedSession::V5(SessionV5::from(session)),
+            VersionedSession::V5(session) => VersionedSession::V6(SessionV6::from(session)),
+            VersionedSession::V6(session) => VersionedSession::Latest(Session::from(session)),
+            VersionedSession::Latest(_) => self,
         }
     }
 
     pub fn latest(self) -> Session {
-        let mut version = self.migrate();
+        let mut version = self;
         loop {
-            if let VersionedSession::V1(session) = version {
+            if let VersionedSession::Latest(session) = version {
                 return session;
             }
             version = version.migrate();
         }
     }
+
+    pub fn from_latest(session: Session) -> Self {
+        VersionedSession::Latest(session)
+    }
+
+    /// Reads what this version understands of a session written by a newer
+    /// client. Fields we don't know are kept, but the session must not be
+    /// saved since newer clients may depend on them being consistent.
+    pub fn newer(data: &[u8]) -> serde_json::Result<Session> {
+        let mut value: serde_json::Value = serde_json::from_slice(data)?;
+        let version = value
+            .as_object_mut()
+            .and_then(|object| object.remove("version"))
+            .and_then(|version| version.as_str().map(String::from));
+        let session: Session = serde_json::from_value(value)?;
+        Ok(Session {
+            newer_version: version,
+            ..session
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::session::State;
+
+    const FIXTURES: [&str; 8] = [
+        include_str!("fixtures/v0.json"),
+        include_str!("fixtures/v1.json"),
+        include_str!("fixtures/v2.json"),
+        include_str!("fixtures/v3.json"),
+        include_str!("fixtures/v4.json"),
+        include_str!("fixtures/v5.json"),
+        include_str!("fixtures/v6.json"),
+        include_str!("fixtures/v7.json"),
+    ];
+
+    #[test]
+    fn fixture_for_every_version() {
+        assert_eq!(FIXTURES.len() as u32, LATEST_VERSION + 1);
+        for (number, fixture) in FIXTURES.iter().enumerate() {
+            let versioned: VersionedSession = serde_json::from_str(fixture).unwrap();
+            assert_eq!(versioned.number(), number as u32);
+        }
+    }
+
+    #[test]
+    fn fixtures_round_trip() {
+        for fixture in FIXTURES {
+            let original: serde_json::Value = serde_json::from_str(fixture).unwrap();
+            let versioned: VersionedSession = serde_json::from_str(fixture).unwrap();
+            let serialized = serde_json::to_value(&versioned).unwrap();
+
+            let version = original["version"].as_str().unwrap();
+            assert_eq!(serialized["version"], version);
+            assert_eq!(serialized["drivers"], original["drivers"], "{}", version);
+            assert_eq!(serialized["branches"], original["branches"], "{}", version);
+            assert_eq!(serialized["state"], original["state"], "{}", version);
+        }
+    }
+
+    #[test]
+    fn fixtures_migrate_to_latest() {
+        for fixture in FIXTURES {
+            let session = serde_json::from_str::<VersionedSession>(fixture)
+                .unwrap()
+                .latest();
+
+            assert_eq!(session.drivers.all(), vec!["alice", "bob"]);
+            assert_eq!(session.branches.branch, "mob-session");
+            assert!(matches!(
+                session.state,
+                State::Working { ref driver, .. } if driver == "alice"
+            ));
+
+            // Saving and loading the migrated session is lossless
+            let saved = serde_json::to_value(VersionedSession::from_latest(session)).unwrap();
+            let loaded: VersionedSession = serde_json::from_value(saved.clone()).unwrap();
+            assert_eq!(serde_json::to_value(loaded).unwrap(), saved);
+        }
+    }
+
+    #[test]
+    fn no_breaks_for_sessions_without_them() {
+        for fixture in &FIXTURES[1..=2] {
+            let session = serde_json::from_str::<VersionedSession>(fixture)
+                .unwrap()
+                .latest();
+
+            let settings = session.settings.unwrap();
+            assert_eq!(settings.break_interval, 0);
+            assert_eq!(settings.lunch(), None);
+        }
+    }
+
+    #[test]
+    fn latest_fixture_round_trips_exactly() {
+        let original: serde_json::Value =
+            serde_json::from_str(FIXTURES[LATEST_VERSION as usize]).unwrap();
+        let versioned: VersionedSession = serde_json::from_value(original.clone()).unwrap();
+
+        assert_eq!(serde_json::to_value(versioned).unwrap(), original);
+    }
+
+    #[test]
+    fn keeps_unknown_fields() {
+        let mut value: serde_json::Value =
+            serde_json::from_str(FIXTURES[LATEST_VERSION as usize]).unwrap();
+        value["from_the_future"] = serde_json::json!({"answer": 42});
+        value["settings"]["from_the_future"] = serde_json::json!(true);
+
+        let versioned: VersionedSession = serde_json::from_value(value.clone()).unwrap();
+        assert_eq!(serde_json::to_value(versioned).unwrap(), value);
+    }
+
+    #[test]
+    fn newer_version_read_only() {
+        let mut value: serde_json::Value =
+            serde_json::from_str(FIXTURES[LATEST_VERSION as usize]).unwrap();
+        value["version"] = serde_json::json!("v99");
+        value["from_the_future"] = serde_json::json!(true);
+
+        let data = serde_json::to_vec(&value).unwrap();
+        let session = VersionedSession::newer(&data).unwrap();
+
+        assert_eq!(session.newer_version, Some("v99".to_string()));
+        assert_eq!(session.drivers.all(), vec!["alice", "bob"]);
+        assert_eq!(session.extra["from_the_future"], serde_json::json!(true));
+    }
+
+    #[test]
+    fn version_numbers() {
+        assert_eq!(Versioned::number("v4"), Some(4));
+        assert_eq!(Versioned::number("four"), None);
+        assert!(Versioned::is_known("v0"));
+        assert!(!Versioned::is_known(&format!("v{}", LATEST_VERSION + 1)));
+    }
+
+    #[test]
+    fn latest_tag_matches_version() {
+        let saved =
+            serde_json::to_value(VersionedSession::from_latest(Session::default())).unwrap();
+        assert_eq!(saved["version"], format!("v{}", LATEST_VERSION));
+    }
 }
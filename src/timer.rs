@@ -3,11 +3,20 @@ use anyhow::Result;
 use chrono;
 use console::Term;
 use std::thread;
+use std::time::Instant;
 
 const FONT_HEIGHT: usize = 7;
 
-pub fn start(title: &str, duration: chrono::Duration) -> Result<()> {
+/// Shows a countdown, calling `heartbeat` every `interval` while it runs
+pub fn start(
+    title: &str,
+    duration: chrono::Duration,
+    interval: chrono::Duration,
+    heartbeat: &mut dyn FnMut(),
+) -> Result<()> {
+    let started = Instant::now();
     let mut time_left = duration;
+    let mut next_heartbeat = interval;
     let second = chrono::Duration::seconds(1);
 
     let term = Term::stdout();
@@ -22,7 +31,14 @@ pub fn start(title: &str, duration: chrono::Duration) -> Result<()> {
         }
         print_ascii(&term, &letters)?;
         thread::sleep(second.to_std()?);
-        time_left = time_left - second;
+
+        // Count from the start so slow heartbeats don't delay the timer
+        let elapsed = chrono::Duration::from_std(started.elapsed())?;
+        if elapsed >= next_heartbeat && elapsed < duration {
+            heartbeat();
+            next_heartbeat = next_heartbeat + interval;
+        }
+        time_left = duration - chrono::Duration::seconds(elapsed.num_seconds());
     }
     term.clear_last_lines(1)?;
 
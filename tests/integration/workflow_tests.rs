@@ -1,4 +1,5 @@
-use crate::fixtures::setup_repos;
+use crate::fixtures::{TestRepos, setup_repos};
+use remotemob::git::Git;
 use remotemob::prompt::MockPrompter;
 use remotemob::session::Store;
 use remotemob::{cmd, config, git, session};
@@ -6,8 +7,14 @@ use remotemob::{cmd, config, git, session};
 fn create_test_config(name: &str) -> config::Config {
     config::Config {
         name: name.to_string(),
+        email: None,
+        id: None,
         remote: "origin".to_string(),
+        meta_dir: None,
+        bundle_dir: None,
+        sync_rebase: false,
         hooks: config::Hooks::new(None),
+        dry_run: false,
     }
 }
 
@@ -38,7 +45,7 @@ fn test_mob_start_creates_session() {
     let session = store.load().unwrap();
     assert!(matches!(
         session.state,
-        session::State::Working { driver } if driver == "alice"
+        session::State::Working { driver, .. } if driver == "alice"
     ));
 }
 
@@ -81,6 +88,7 @@ fn test_start_with_dirty_tree_discard() {
     let alice = repos.alice();
 
     alice.write_file("dirty.txt", "uncommitted");
+    alice.write_file("README.md", "# Changed\n");
 
     let config = create_test_config("alice");
 
@@ -101,6 +109,55 @@ fn test_start_with_dirty_tree_discard() {
 
     // Verify file was discarded
     assert!(!alice.file_exists("dirty.txt"));
+    assert_eq!(alice.read_file("README.md"), "# Test\n");
+
+    // The discarded changes are backed up
+    let backup = alice.git_ok(&["for-each-ref", "--format=%(refname)", "refs/mob/backup"]);
+    assert!(backup.ends_with("/HEAD"));
+    assert_eq!(
+        alice.git_ok(&["show", &format!("{}:dirty.txt", backup)]),
+        "uncommitted"
+    );
+    assert_eq!(
+        alice.git_ok(&["show", &format!("{}:README.md", backup)]),
+        "# Changed"
+    );
+    assert_eq!(
+        alice.git_ok(&["rev-parse", &format!("{}^", backup)]),
+        alice.git_ok(&["rev-parse", "main"])
+    );
+}
+
+#[test]
+fn test_start_with_dirty_tree_discard_keeps_ignored() {
+    let repos = setup_repos(1);
+    let alice = repos.alice();
+
+    alice.write_file(".git/info/exclude", "*.log\n");
+    alice.write_file("build.log", "ignored");
+    alice.write_file("dirty.txt", "uncommitted");
+
+    let prompter = MockPrompter::new()
+        .with_selection(2) // Discard changes
+        .with_string("mob-session")
+        .with_string("main")
+        .with_number(10)
+        .with_string("mob sync");
+
+    let git = git::GitCommand::new(Some(alice.path.clone()), "origin".to_string()).unwrap();
+    let store = session::SessionStore::new(&git);
+    cmd::Start::new(
+        &git,
+        &store,
+        &prompter,
+        cmd::StartOpts { minutes: Some(0) },
+        create_test_config("alice"),
+    )
+    .run()
+    .unwrap();
+
+    assert!(!alice.file_exists("dirty.txt"));
+    assert_eq!(alice.read_file("build.log"), "ignored");
 }
 
 #[test]
@@ -133,9 +190,14 @@ fn test_full_workflow_alice_to_bob() {
     alice.write_file("alice.txt", "Alice's work");
 
     // Alice runs next
-    cmd::Next::new(&alice_git, &alice_store, create_test_config("alice"))
-        .run()
-        .unwrap();
+    cmd::Next::new(
+        &alice_git,
+        &alice_store,
+        &MockPrompter::new(),
+        create_test_config("alice"),
+    )
+    .run()
+    .unwrap();
 
     // Verify state is WaitingForNext
     let session = alice_store.load().unwrap();
@@ -164,7 +226,7 @@ fn test_full_workflow_alice_to_bob() {
     let session = bob_store.load().unwrap();
     assert!(matches!(
         session.state,
-        session::State::Working { driver } if driver == "bob"
+        session::State::Working { driver, .. } if driver == "bob"
     ));
 
     // Verify alice's file is present
@@ -177,6 +239,7 @@ fn test_full_workflow_alice_to_bob() {
         &bob_git,
         &bob_store,
         &bob_done_prompter,
+        cmd::DoneOpts::default(),
         create_test_config("bob"),
     )
     .run()
@@ -187,6 +250,85 @@ fn test_full_workflow_alice_to_bob() {
     assert_eq!(branch, "main");
 }
 
+#[test]
+fn test_history_records_turns() {
+    let repos = setup_repos(2);
+    let alice = repos.alice();
+    let bob = repos.bob();
+
+    let alice_git = git::GitCommand::new(Some(alice.path.clone()), "origin".to_string()).unwrap();
+    let alice_store = session::SessionStore::new(&alice_git);
+    let bob_git = git::GitCommand::new(Some(bob.path.clone()), "origin".to_string()).unwrap();
+    let bob_store = session::SessionStore::new(&bob_git);
+
+    let prompter = MockPrompter::new()
+        .with_string("mob-session")
+        .with_string("main")
+        .with_number(10)
+        .with_string("mob sync");
+    cmd::Start::new(
+        &alice_git,
+        &alice_store,
+        &prompter,
+        cmd::StartOpts { minutes: Some(0) },
+        create_test_config("alice"),
+    )
+    .run()
+    .unwrap();
+    let before = alice.git_ok(&["rev-parse", "HEAD"]);
+    alice.write_file("alice.txt", "Alice's work");
+    cmd::Next::new(
+        &alice_git,
+        &alice_store,
+        &MockPrompter::new(),
+        create_test_config("alice"),
+    )
+    .run()
+    .unwrap();
+    cmd::Start::new(
+        &bob_git,
+        &bob_store,
+        &MockPrompter::new(),
+        cmd::StartOpts { minutes: Some(0) },
+        create_test_config("bob"),
+    )
+    .run()
+    .unwrap();
+    cmd::Done::new(
+        &bob_git,
+        &bob_store,
+        &MockPrompter::new(),
+        cmd::DoneOpts::default(),
+        create_test_config("bob"),
+    )
+    .run()
+    .unwrap();
+
+    // Every turn is recorded, also after the session is done
+    let history = alice_store.history().unwrap();
+    let events: Vec<(&str, &session::EventKind)> = history
+        .events()
+        .iter()
+        .map(|event| (event.driver.as_str(), &event.kind))
+        .collect();
+    assert!(matches!(
+        events.as_slice(),
+        [
+            ("alice", session::EventKind::Start),
+            ("alice", session::EventKind::Next { next: Some(next), before: Some(commit), .. }),
+            ("bob", session::EventKind::Start),
+            ("bob", session::EventKind::Done),
+        ] if next == "alice" && *commit == before
+    ));
+    assert!(history.events().iter().all(|event| event.commit.is_some()));
+
+    let turns = history.turns();
+    assert_eq!(turns.len(), 2);
+    assert_eq!(turns[0].driver, "alice");
+    assert_eq!(turns[0].from_commit, Some(before));
+    assert_eq!(turns[1].driver, "bob");
+}
+
 #[test]
 fn test_driver_rotation() {
     let repos = setup_repos(3);
@@ -215,9 +357,14 @@ fn test_driver_rotation() {
     .unwrap();
 
     // Alice runs next
-    cmd::Next::new(&alice_git, &alice_store, create_test_config("alice"))
-        .run()
-        .unwrap();
+    cmd::Next::new(
+        &alice_git,
+        &alice_store,
+        &MockPrompter::new(),
+        create_test_config("alice"),
+    )
+    .run()
+    .unwrap();
 
     // Bob joins
     let bob_prompter = MockPrompter::new();
@@ -238,13 +385,18 @@ fn test_driver_rotation() {
     let session = bob_store.load().unwrap();
     assert!(matches!(
         session.state,
-        session::State::Working { driver } if driver == "bob"
+        session::State::Working { driver, .. } if driver == "bob"
     ));
 
     // Bob runs next
-    cmd::Next::new(&bob_git, &bob_store, create_test_config("bob"))
-        .run()
-        .unwrap();
+    cmd::Next::new(
+        &bob_git,
+        &bob_store,
+        &MockPrompter::new(),
+        create_test_config("bob"),
+    )
+    .run()
+    .unwrap();
 
     // Carol joins
     let carol_prompter = MockPrompter::new();
@@ -265,7 +417,7 @@ fn test_driver_rotation() {
     let session = carol_store.load().unwrap();
     assert!(matches!(
         session.state,
-        session::State::Working { driver } if driver == "carol"
+        session::State::Working { driver, .. } if driver == "carol"
     ));
 
     // Verify all three are in the drivers list
@@ -274,4 +426,2174 @@ fn test_driver_rotation() {
     assert!(drivers.contains(&"alice".to_string()));
     assert!(drivers.contains(&"bob".to_string()));
     assert!(drivers.contains(&"carol".to_string()));
+
+    // The next driver navigates
+    assert_eq!(session.navigator, Some("alice".to_string()));
+}
+
+#[test]
+fn test_break_blocks_start() {
+    let repos = setup_repos(2);
+    let alice = repos.alice();
+    let bob = repos.bob();
+
+    let alice_prompter = MockPrompter::new()
+        .with_string("mob-session")
+        .with_string("main")
+        .with_number(10)
+        .with_string("mob sync");
+
+    let alice_git = git::GitCommand::new(Some(alice.path.clone()), "origin".to_string()).unwrap();
+    let alice_store = session::SessionStore::new(&alice_git);
+
+    cmd::Start::new(
+        &alice_git,
+        &alice_store,
+        &alice_prompter,
+        cmd::StartOpts { minutes: Some(0) },
+        create_test_config("alice"),
+    )
+    .run()
+    .unwrap();
+
+    // Pretend the mob has been going for an hour
+    let session = alice_store.load().unwrap();
+    alice_store
+        .save(session::Session {
+            mob_time: 60,
+            ..session
+        })
+        .unwrap();
+
+    cmd::Next::new(
+        &alice_git,
+        &alice_store,
+        &MockPrompter::new(),
+        create_test_config("alice"),
+    )
+    .run()
+    .unwrap();
+
+    let session = alice_store.load().unwrap();
+    assert!(matches!(session.state, session::State::Break { .. }));
+    assert_eq!(session.mob_time, 0);
+
+    // Bob can't start during the break
+    let bob_git = git::GitCommand::new(Some(bob.path.clone()), "origin".to_string()).unwrap();
+    let bob_store = session::SessionStore::new(&bob_git);
+
+    cmd::Start::new(
+        &bob_git,
+        &bob_store,
+        &MockPrompter::new(),
+        cmd::StartOpts { minutes: Some(0) },
+        create_test_config("bob"),
+    )
+    .run()
+    .unwrap();
+
+    let session = bob_store.load().unwrap();
+    assert!(matches!(session.state, session::State::Break { .. }));
+}
+
+#[test]
+fn test_log_turns() {
+    let repos = setup_repos(1);
+    let alice = repos.alice();
+
+    let prompter = MockPrompter::new()
+        .with_string("mob-session")
+        .with_string("main")
+        .with_number(10)
+        .with_string("mob sync");
+
+    let git = git::GitCommand::new(Some(alice.path.clone()), "origin".to_string()).unwrap();
+    let store = session::SessionStore::new(&git);
+
+    cmd::Start::new(
+        &git,
+        &store,
+        &prompter,
+        cmd::StartOpts { minutes: Some(0) },
+        create_test_config("alice"),
+    )
+    .run()
+    .unwrap();
+
+    alice.write_file("alice.txt", "Alice's work");
+
+    cmd::Next::new(
+        &git,
+        &store,
+        &MockPrompter::new(),
+        create_test_config("alice"),
+    )
+    .run()
+    .unwrap();
+
+    let turns = store.history().unwrap().turns();
+    assert_eq!(turns.len(), 1);
+
+    let turn = &turns[0];
+    let files = git
+        .changed_files(
+            turn.from_commit.as_ref().unwrap(),
+            turn.to_commit.as_ref().unwrap(),
+        )
+        .unwrap();
+    assert_eq!(files, vec!["alice.txt".to_string()]);
+
+    let opts = cmd::LogOpts {
+        driver: Some("alice".to_string()),
+        since: Some("1h".to_string()),
+        until: None,
+        json: true,
+    };
+    cmd::Log::new(opts, &git, &store).run().unwrap();
+}
+
+#[test]
+fn test_named_sessions() {
+    let repos = setup_repos(2);
+    let alice = repos.alice();
+    let bob = repos.bob();
+
+    // Alice uses the default session
+    let alice_prompter = MockPrompter::new()
+        .with_string("mob-session")
+        .with_string("main")
+        .with_number(10)
+        .with_string("mob sync");
+
+    let alice_git = git::GitCommand::new(Some(alice.path.clone()), "origin".to_string()).unwrap();
+    let alice_store = session::SessionStore::new(&alice_git);
+
+    cmd::Start::new(
+        &alice_git,
+        &alice_store,
+        &alice_prompter,
+        cmd::StartOpts { minutes: Some(0) },
+        create_test_config("alice"),
+    )
+    .run()
+    .unwrap();
+
+    // Bob starts a named session on another branch
+    let bob_prompter = MockPrompter::new()
+        .with_string("other-session")
+        .with_string("main")
+        .with_number(10)
+        .with_string("mob sync");
+
+    let bob_git = git::GitCommand::new(Some(bob.path.clone()), "origin".to_string())
+        .unwrap()
+        .with_session(Some("other".to_string()))
+        .unwrap();
+    let bob_store = session::SessionStore::new(&bob_git);
+
+    cmd::Start::new(
+        &bob_git,
+        &bob_store,
+        &bob_prompter,
+        cmd::StartOpts { minutes: Some(0) },
+        create_test_config("bob"),
+    )
+    .run()
+    .unwrap();
+
+    // Both sessions are intact
+    let session = alice_store.load().unwrap();
+    assert!(matches!(
+        session.state,
+        session::State::Working { driver, .. } if driver == "alice"
+    ));
+
+    let sessions = alice_store.load_all().unwrap();
+    let names: Vec<&str> = sessions.iter().map(|(name, _)| name.as_str()).collect();
+    assert_eq!(names, vec!["default", "other"]);
+    assert_eq!(sessions[1].1.branches.branch, "other-session");
+
+    // Cleaning one session leaves the other
+    bob_store.clean().unwrap();
+    let sessions = alice_store.load_all().unwrap();
+    assert_eq!(sessions.len(), 1);
+    assert_eq!(sessions[0].0, "default");
+}
+
+#[test]
+fn test_named_session_branches() {
+    let repos = setup_repos(2);
+    let alice = repos.alice();
+    let bob = repos.bob();
+
+    let alice_git = git::GitCommand::new(Some(alice.path.clone()), "origin".to_string()).unwrap();
+    let alice_store = session::SessionStore::new(&alice_git);
+    cmd::Start::new(
+        &alice_git,
+        &alice_store,
+        &MockPrompter::new(),
+        cmd::StartOpts { minutes: Some(0) },
+        create_test_config("alice"),
+    )
+    .run()
+    .unwrap();
+
+    // A named session gets a branch of its own by default
+    let bob_git = |session: &str| {
+        git::GitCommand::new(Some(bob.path.clone()), "origin".to_string())
+            .unwrap()
+            .with_session(Some(session.to_string()))
+            .unwrap()
+    };
+    let search_git = bob_git("search");
+    let search_store = session::SessionStore::new(&search_git);
+    cmd::Start::new(
+        &search_git,
+        &search_store,
+        &MockPrompter::new(),
+        cmd::StartOpts { minutes: Some(0) },
+        create_test_config("bob"),
+    )
+    .run()
+    .unwrap();
+    assert_eq!(search_store.load().unwrap().branches.branch, "mob-search");
+    assert_eq!(alice_store.load().unwrap().branches.branch, "mob-session");
+
+    // Another session can't take a branch that is in use
+    let review_git = bob_git("review");
+    let review_store = session::SessionStore::new(&review_git);
+    let prompter = MockPrompter::new()
+        .with_string("mob-session")
+        .with_string("main")
+        .with_string("mob sync");
+    assert!(
+        cmd::Start::new(
+            &review_git,
+            &review_store,
+            &prompter,
+            cmd::StartOpts { minutes: Some(0) },
+            create_test_config("bob"),
+        )
+        .run()
+        .is_err()
+    );
+    assert_eq!(review_store.load_all().unwrap().len(), 2);
+
+    // Nor a branch with session data
+    let prompter = MockPrompter::new()
+        .with_string("mob-meta-search")
+        .with_string("main")
+        .with_string("mob sync");
+    let err = cmd::Start::new(
+        &review_git,
+        &review_store,
+        &prompter,
+        cmd::StartOpts { minutes: Some(0) },
+        create_test_config("bob"),
+    )
+    .run()
+    .unwrap_err();
+    assert!(err.to_string().contains("session data"), "{}", err);
+
+    // Names that would clash with session data or make no branch are refused
+    for name in ["meta", "meta-search", "two..dots", "with space"] {
+        assert!(
+            git::GitCommand::new(Some(bob.path.clone()), "origin".to_string())
+                .unwrap()
+                .with_session(Some(name.to_string()))
+                .is_err(),
+            "{}",
+            name
+        );
+    }
+}
+
+#[test]
+fn test_pick_next_driver_at_handover() {
+    let repos = setup_repos(1);
+    let alice = repos.alice();
+
+    let prompter = MockPrompter::new()
+        .with_selection(3) // pick at handover
+        .with_selection(0) // next driver navigates
+        .with_string("mob-session")
+        .with_string("main")
+        .with_number(10)
+        .with_string("mob sync");
+
+    let git = git::GitCommand::new(Some(alice.path.clone()), "origin".to_string()).unwrap();
+    let store = session::SessionStore::new(&git);
+
+    cmd::Start::new(
+        &git,
+        &store,
+        &prompter,
+        cmd::StartOpts { minutes: Some(0) },
+        create_test_config("alice"),
+    )
+    .run()
+    .unwrap();
+
+    let session = store.load().unwrap();
+    assert_eq!(
+        session.settings.as_ref().unwrap().rotation,
+        session::Rotation::Pick
+    );
+    store
+        .save(session::Session {
+            drivers: session::Drivers::new(vec![
+                "alice".to_string(),
+                "bob".to_string(),
+                "carol".to_string(),
+            ]),
+            ..session
+        })
+        .unwrap();
+
+    // Alice skips bob and picks carol
+    let prompter = MockPrompter::new().with_selection(2);
+    cmd::Next::new(&git, &store, &prompter, create_test_config("alice"))
+        .run()
+        .unwrap();
+
+    let session = store.load().unwrap();
+    assert!(matches!(
+        session.state,
+        session::State::WaitingForNext { next: Some(next) } if next == "carol"
+    ));
+}
+
+#[test]
+fn test_no_navigator_alone() {
+    let repos = setup_repos(1);
+    let alice = repos.alice();
+
+    let prompter = MockPrompter::new()
+        .with_string("mob-session")
+        .with_string("main")
+        .with_number(10)
+        .with_string("mob sync");
+
+    let git = git::GitCommand::new(Some(alice.path.clone()), "origin".to_string()).unwrap();
+    let store = session::SessionStore::new(&git);
+
+    cmd::Start::new(
+        &git,
+        &store,
+        &prompter,
+        cmd::StartOpts { minutes: Some(0) },
+        create_test_config("alice"),
+    )
+    .run()
+    .unwrap();
+    cmd::Next::new(
+        &git,
+        &store,
+        &MockPrompter::new(),
+        create_test_config("alice"),
+    )
+    .run()
+    .unwrap();
+
+    // Alice drives next, there is nobody else to navigate
+    let session = store.load().unwrap();
+    assert!(matches!(
+        session.state,
+        session::State::WaitingForNext { next: Some(ref next) } if next == "alice"
+    ));
+    assert_eq!(session.navigator, None);
+}
+
+#[test]
+fn test_random_next_driver_navigates() {
+    let repos = setup_repos(1);
+    let alice = repos.alice();
+
+    let prompter = MockPrompter::new()
+        .with_selection(1) // random
+        .with_selection(0); // next driver navigates
+
+    let git = git::GitCommand::new(Some(alice.path.clone()), "origin".to_string()).unwrap();
+    let store = session::SessionStore::new(&git);
+
+    cmd::Start::new(
+        &git,
+        &store,
+        &prompter,
+        cmd::StartOpts { minutes: Some(0) },
+        create_test_config("alice"),
+    )
+    .run()
+    .unwrap();
+
+    let session = store.load().unwrap();
+    let session = session::Session {
+        drivers: session::Drivers::new(
+            ["alice", "bob", "carol", "dave", "erin"]
+                .map(String::from)
+                .to_vec(),
+        ),
+        ..session
+    }
+    .with_navigator(&store.history().unwrap());
+    let navigator = session.navigator.clone().unwrap();
+    store.save(session).unwrap();
+
+    // The navigator was told they drive next
+    cmd::Next::new(
+        &git,
+        &store,
+        &MockPrompter::new(),
+        create_test_config("alice"),
+    )
+    .run()
+    .unwrap();
+
+    let session = store.load().unwrap();
+    assert!(matches!(
+        session.state,
+        session::State::WaitingForNext { next: Some(next) } if next == navigator
+    ));
+}
+
+#[test]
+fn test_away_driver_is_skipped() {
+    let repos = setup_repos(2);
+    let alice = repos.alice();
+    let bob = repos.bob();
+
+    let alice_git = git::GitCommand::new(Some(alice.path.clone()), "origin".to_string()).unwrap();
+    let alice_store = session::SessionStore::new(&alice_git);
+    let bob_git = git::GitCommand::new(Some(bob.path.clone()), "origin".to_string()).unwrap();
+    let bob_store = session::SessionStore::new(&bob_git);
+
+    let alice_prompter = MockPrompter::new()
+        .with_string("mob-session")
+        .with_string("main")
+        .with_number(10)
+        .with_string("mob sync");
+    cmd::Start::new(
+        &alice_git,
+        &alice_store,
+        &alice_prompter,
+        cmd::StartOpts { minutes: Some(0) },
+        create_test_config("alice"),
+    )
+    .run()
+    .unwrap();
+    cmd::Next::new(
+        &alice_git,
+        &alice_store,
+        &MockPrompter::new(),
+        create_test_config("alice"),
+    )
+    .run()
+    .unwrap();
+
+    cmd::Start::new(
+        &bob_git,
+        &bob_store,
+        &MockPrompter::new(),
+        cmd::StartOpts { minutes: Some(0) },
+        create_test_config("bob"),
+    )
+    .run()
+    .unwrap();
+    cmd::Next::new(
+        &bob_git,
+        &bob_store,
+        &MockPrompter::new(),
+        create_test_config("bob"),
+    )
+    .run()
+    .unwrap();
+
+    let session = bob_store.load().unwrap();
+    assert!(matches!(
+        session.state,
+        session::State::WaitingForNext { next: Some(ref next) } if next == "alice"
+    ));
+
+    // Alice is in a meeting, bob marks her as away
+    cmd::Presence::away(
+        cmd::PresenceOpts {
+            name: Some("alice".to_string()),
+        },
+        &bob_store,
+        create_test_config("bob"),
+    )
+    .run()
+    .unwrap();
+
+    let session = bob_store.load().unwrap();
+    assert!(session.drivers.is_away("alice"));
+    assert_eq!(session.drivers.all(), vec!["alice", "bob"]);
+    assert!(matches!(
+        session.state,
+        session::State::WaitingForNext { next: Some(ref next) } if next == "bob"
+    ));
+
+    // Unknown drivers are refused
+    assert!(
+        cmd::Presence::away(
+            cmd::PresenceOpts {
+                name: Some("mallory".to_string()),
+            },
+            &bob_store,
+            create_test_config("bob"),
+        )
+        .run()
+        .is_err()
+    );
+
+    // Alice comes back and keeps her place
+    cmd::Presence::back(
+        cmd::PresenceOpts { name: None },
+        &alice_store,
+        create_test_config("alice"),
+    )
+    .run()
+    .unwrap();
+
+    let session = alice_store.load().unwrap();
+    assert!(!session.drivers.is_away("alice"));
+    assert_eq!(session.drivers.next("bob"), Some("alice".to_string()));
+}
+
+#[test]
+fn test_join_and_leave() {
+    let repos = setup_repos(3);
+    let alice = repos.alice();
+    let bob = repos.bob();
+    let carol = repos.carol();
+
+    let alice_git = git::GitCommand::new(Some(alice.path.clone()), "origin".to_string()).unwrap();
+    let alice_store = session::SessionStore::new(&alice_git);
+    let bob_git = git::GitCommand::new(Some(bob.path.clone()), "origin".to_string()).unwrap();
+    let bob_store = session::SessionStore::new(&bob_git);
+    let carol_git = git::GitCommand::new(Some(carol.path.clone()), "origin".to_string()).unwrap();
+    let carol_store = session::SessionStore::new(&carol_git);
+
+    let alice_prompter = MockPrompter::new()
+        .with_string("mob-session")
+        .with_string("main")
+        .with_number(10)
+        .with_string("mob sync");
+    cmd::Start::new(
+        &alice_git,
+        &alice_store,
+        &alice_prompter,
+        cmd::StartOpts { minutes: Some(0) },
+        create_test_config("alice"),
+    )
+    .run()
+    .unwrap();
+    cmd::Next::new(
+        &alice_git,
+        &alice_store,
+        &MockPrompter::new(),
+        create_test_config("alice"),
+    )
+    .run()
+    .unwrap();
+
+    // Bob joins without taking a turn
+    cmd::Join::new(
+        cmd::JoinOpts {
+            after: Some("alice".to_string()),
+        },
+        &bob_store,
+        &MockPrompter::new(),
+        create_test_config("bob"),
+    )
+    .run()
+    .unwrap();
+
+    // Carol picks her place
+    cmd::Join::new(
+        cmd::JoinOpts { after: None },
+        &carol_store,
+        &MockPrompter::new().with_selection(0),
+        create_test_config("carol"),
+    )
+    .run()
+    .unwrap();
+
+    let session = carol_store.load().unwrap();
+    assert_eq!(session.drivers.all(), vec!["alice", "carol", "bob"]);
+    assert!(matches!(
+        session.state,
+        session::State::WaitingForNext { next: Some(ref next) } if next == "alice"
+    ));
+    // Joining does not touch git
+    assert_eq!(bob.git_ok(&["branch", "--show-current"]), "main");
+
+    // Alice was next, so carol is next when she leaves
+    cmd::Leave::new(&alice_store, create_test_config("alice"))
+        .run()
+        .unwrap();
+
+    let session = alice_store.load().unwrap();
+    assert_eq!(session.drivers.all(), vec!["carol", "bob"]);
+    assert!(matches!(
+        session.state,
+        session::State::WaitingForNext { next: Some(ref next) } if next == "carol"
+    ));
+    assert_eq!(session.navigator, Some("bob".to_string()));
+
+    let kinds: Vec<session::EventKind> = alice_store
+        .history()
+        .unwrap()
+        .events()
+        .iter()
+        .map(|event| event.kind.clone())
+        .collect();
+    assert_eq!(
+        kinds[kinds.len() - 3..],
+        [
+            session::EventKind::Join,
+            session::EventKind::Join,
+            session::EventKind::Leave
+        ]
+    );
+}
+
+#[test]
+fn test_skip_turn() {
+    let repos = setup_repos(2);
+    let alice = repos.alice();
+    let bob = repos.bob();
+
+    let alice_git = git::GitCommand::new(Some(alice.path.clone()), "origin".to_string()).unwrap();
+    let alice_store = session::SessionStore::new(&alice_git);
+    let bob_git = git::GitCommand::new(Some(bob.path.clone()), "origin".to_string()).unwrap();
+    let bob_store = session::SessionStore::new(&bob_git);
+
+    let alice_prompter = MockPrompter::new()
+        .with_string("mob-session")
+        .with_string("main")
+        .with_number(10)
+        .with_string("mob sync");
+    cmd::Start::new(
+        &alice_git,
+        &alice_store,
+        &alice_prompter,
+        cmd::StartOpts { minutes: Some(0) },
+        create_test_config("alice"),
+    )
+    .run()
+    .unwrap();
+
+    cmd::Join::new(
+        cmd::JoinOpts {
+            after: Some("alice".to_string()),
+        },
+        &bob_store,
+        &MockPrompter::new(),
+        create_test_config("bob"),
+    )
+    .run()
+    .unwrap();
+
+    cmd::Next::new(
+        &alice_git,
+        &alice_store,
+        &MockPrompter::new(),
+        create_test_config("alice"),
+    )
+    .run()
+    .unwrap();
+
+    // Alice can't skip bob's turn
+    cmd::Skip::new(&alice_store, create_test_config("alice"))
+        .run()
+        .unwrap();
+    let session = alice_store.load().unwrap();
+    assert!(matches!(
+        session.state,
+        session::State::WaitingForNext { next: Some(ref next) } if next == "bob"
+    ));
+
+    cmd::Skip::new(&bob_store, create_test_config("bob"))
+        .run()
+        .unwrap();
+
+    let session = bob_store.load().unwrap();
+    assert!(matches!(
+        session.state,
+        session::State::WaitingForNext { next: Some(ref next) } if next == "alice"
+    ));
+    assert_eq!(session.navigator, Some("bob".to_string()));
+    assert_eq!(bob.git_ok(&["branch", "--show-current"]), "main");
+
+    let history = bob_store.history().unwrap();
+    let last = history.events().last().unwrap();
+    assert_eq!(last.driver, "bob");
+    assert_eq!(
+        last.kind,
+        session::EventKind::Skip {
+            next: Some("alice".to_string())
+        }
+    );
+}
+
+#[test]
+fn test_pause_and_resume() {
+    let repos = setup_repos(2);
+    let alice = repos.alice();
+    let bob = repos.bob();
+
+    let alice_git = git::GitCommand::new(Some(alice.path.clone()), "origin".to_string()).unwrap();
+    let alice_store = session::SessionStore::new(&alice_git);
+    let bob_git = git::GitCommand::new(Some(bob.path.clone()), "origin".to_string()).unwrap();
+    let bob_store = session::SessionStore::new(&bob_git);
+
+    let alice_prompter = MockPrompter::new()
+        .with_string("mob-session")
+        .with_string("main")
+        .with_number(10)
+        .with_string("mob sync");
+    cmd::Start::new(
+        &alice_git,
+        &alice_store,
+        &alice_prompter,
+        cmd::StartOpts { minutes: Some(0) },
+        create_test_config("alice"),
+    )
+    .run()
+    .unwrap();
+
+    cmd::Pause::new(
+        cmd::PauseOpts {
+            reason: Some("lunch".to_string()),
+            duration: Some("45m".to_string()),
+        },
+        &bob_store,
+        create_test_config("bob"),
+    )
+    .run()
+    .unwrap();
+
+    let session = bob_store.load().unwrap();
+    match &session.state {
+        session::State::Paused { reason, until, .. } => {
+            assert_eq!(reason.as_deref(), Some("lunch"));
+            assert!(until.is_some());
+        }
+        other => panic!("Expected paused but got {:?}", other),
+    }
+
+    // Neither next nor start changes a paused session
+    alice.write_file("lunch.txt", "sandwich");
+    cmd::Next::new(
+        &alice_git,
+        &alice_store,
+        &MockPrompter::new(),
+        create_test_config("alice"),
+    )
+    .run()
+    .unwrap();
+    cmd::Start::new(
+        &bob_git,
+        &bob_store,
+        &MockPrompter::new(),
+        cmd::StartOpts { minutes: Some(0) },
+        create_test_config("bob"),
+    )
+    .run()
+    .unwrap();
+
+    let session = alice_store.load().unwrap();
+    assert!(matches!(session.state, session::State::Paused { .. }));
+    assert!(alice.file_exists("lunch.txt"));
+    assert!(!alice.git_ok(&["status", "--porcelain"]).is_empty());
+
+    cmd::Resume::new(&alice_store, create_test_config("alice"))
+        .run()
+        .unwrap();
+
+    let session = alice_store.load().unwrap();
+    assert!(matches!(
+        session.state,
+        session::State::Working { ref driver, .. } if driver == "alice"
+    ));
+}
+
+#[test]
+fn test_take_over_expired_lease() {
+    let repos = setup_repos(2);
+    let alice = repos.alice();
+    let bob = repos.bob();
+
+    let alice_git = git::GitCommand::new(Some(alice.path.clone()), "origin".to_string()).unwrap();
+    let alice_store = session::SessionStore::new(&alice_git);
+    let bob_git = git::GitCommand::new(Some(bob.path.clone()), "origin".to_string()).unwrap();
+    let bob_store = session::SessionStore::new(&bob_git);
+
+    let alice_prompter = MockPrompter::new()
+        .with_string("mob-session")
+        .with_string("main")
+        .with_number(10)
+        .with_string("mob sync");
+    cmd::Start::new(
+        &alice_git,
+        &alice_store,
+        &alice_prompter,
+        cmd::StartOpts { minutes: Some(0) },
+        create_test_config("alice"),
+    )
+    .run()
+    .unwrap();
+
+    // Alice's laptop died during a turn that ended a while ago
+    let session = alice_store.load().unwrap();
+    let last_seen = chrono::Utc::now() - chrono::Duration::minutes(session::LEASE_DURATION + 5);
+    assert!(!session.state.lease_expired(chrono::Utc::now()));
+    alice_store
+        .save(session::Session {
+            state: session::State::Working {
+                driver: "alice".to_string(),
+                started: last_seen,
+                work_duration: 0,
+                last_seen: Some(last_seen),
+            },
+            ..session
+        })
+        .unwrap();
+
+    // The first choice is to take over, not retry
+    cmd::Start::new(
+        &bob_git,
+        &bob_store,
+        &MockPrompter::new().with_selection(0),
+        cmd::StartOpts { minutes: Some(0) },
+        create_test_config("bob"),
+    )
+    .run()
+    .unwrap();
+
+    let session = bob_store.load().unwrap();
+    assert!(matches!(
+        session.state,
+        session::State::Working { ref driver, .. } if driver == "bob"
+    ));
+}
+
+#[test]
+fn test_change_settings() {
+    let repos = setup_repos(1);
+    let alice = repos.alice();
+
+    let alice_git = git::GitCommand::new(Some(alice.path.clone()), "origin".to_string()).unwrap();
+    let alice_store = session::SessionStore::new(&alice_git);
+
+    let alice_prompter = MockPrompter::new()
+        .with_string("mob-session")
+        .with_string("main")
+        .with_number(10)
+        .with_string("mob sync");
+    cmd::Start::new(
+        &alice_git,
+        &alice_store,
+        &alice_prompter,
+        cmd::StartOpts { minutes: Some(0) },
+        create_test_config("alice"),
+    )
+    .run()
+    .unwrap();
+
+    cmd::Settings::new(
+        cmd::SettingsOpts {
+            work_duration: Some(25),
+            rotation: Some(session::Rotation::Random),
+            ..Default::default()
+        },
+        &alice_store,
+        &MockPrompter::new(),
+    )
+    .run()
+    .unwrap();
+
+    let session = alice_store.load().unwrap();
+    let settings = session.settings.unwrap();
+    assert_eq!(settings.work_duration, 25);
+    assert_eq!(settings.rotation, session::Rotation::Random);
+    assert_eq!(settings.commit_message, "mob sync");
+    assert_eq!(session.drivers.all(), vec!["alice"]);
+    assert!(matches!(
+        session.state,
+        session::State::Working { ref driver, .. } if driver == "alice"
+    ));
+
+    // Invalid settings are not saved
+    assert!(
+        cmd::Settings::new(
+            cmd::SettingsOpts {
+                lunch_start: Some("noon".to_string()),
+                ..Default::default()
+            },
+            &alice_store,
+            &MockPrompter::new(),
+        )
+        .run()
+        .is_err()
+    );
+
+    // Interactively, answers are popped in reverse
+    let prompter = MockPrompter::new()
+        .with_confirm(true)
+        .with_confirm(true)
+        .with_string("12:30")
+        .with_string("11:30")
+        .with_string("wip")
+        .with_number(5)
+        .with_number(50)
+        .with_number(15)
+        .with_selection(0)
+        .with_selection(2);
+    cmd::Settings::new(cmd::SettingsOpts::default(), &alice_store, &prompter)
+        .run()
+        .unwrap();
+
+    let settings = alice_store.load().unwrap().settings.unwrap();
+    assert_eq!(settings.commit_message, "wip");
+    assert_eq!(settings.work_duration, 15);
+    assert_eq!(settings.break_interval, 50);
+    assert_eq!(settings.break_duration, 5);
+    assert_eq!(settings.lunch_start, Some("11:30".to_string()));
+    assert_eq!(settings.lunch_end, Some("12:30".to_string()));
+    assert_eq!(settings.navigator, session::Navigator::Nobody);
+    assert_eq!(settings.rotation, session::Rotation::RoundRobin);
+}
+
+#[test]
+fn test_drivers_with_same_name() {
+    let repos = setup_repos(3);
+    let alice = repos.alice();
+    let bob = repos.bob();
+    let carol = repos.carol();
+
+    let alex = |email: &str| config::Config {
+        email: Some(email.to_string()),
+        ..create_test_config("Alex")
+    };
+
+    let alice_git = git::GitCommand::new(Some(alice.path.clone()), "origin".to_string()).unwrap();
+    let alice_store = session::SessionStore::new(&alice_git);
+    let bob_git = git::GitCommand::new(Some(bob.path.clone()), "origin".to_string()).unwrap();
+    let bob_store = session::SessionStore::new(&bob_git);
+    let carol_git = git::GitCommand::new(Some(carol.path.clone()), "origin".to_string()).unwrap();
+    let carol_store = session::SessionStore::new(&carol_git);
+
+    let prompter = MockPrompter::new()
+        .with_string("mob-session")
+        .with_string("main")
+        .with_number(10)
+        .with_string("mob sync");
+    cmd::Start::new(
+        &alice_git,
+        &alice_store,
+        &prompter,
+        cmd::StartOpts { minutes: Some(0) },
+        alex("alex@one.com"),
+    )
+    .run()
+    .unwrap();
+
+    // Another Alex is a different driver
+    cmd::Join::new(
+        cmd::JoinOpts {
+            after: Some("alex@one.com".to_string()),
+        },
+        &bob_store,
+        &MockPrompter::new(),
+        alex("alex@two.com"),
+    )
+    .run()
+    .unwrap();
+
+    // Carol joins before her email is configured
+    cmd::Join::new(
+        cmd::JoinOpts {
+            after: Some("alex@two.com".to_string()),
+        },
+        &carol_store,
+        &MockPrompter::new(),
+        create_test_config("carol"),
+    )
+    .run()
+    .unwrap();
+
+    let session = carol_store.load().unwrap();
+    assert_eq!(
+        session.drivers.all(),
+        vec!["alex@one.com", "alex@two.com", "carol"]
+    );
+    assert_eq!(session.drivers.name("alex@two.com"), "Alex <alex@two.com>");
+    assert_eq!(
+        session.drivers.next("alex@one.com").unwrap(),
+        "alex@two.com"
+    );
+
+    // With an email she is still the same driver
+    cmd::Presence::away(
+        cmd::PresenceOpts { name: None },
+        &carol_store,
+        config::Config {
+            email: Some("carol@three.com".to_string()),
+            ..create_test_config("carol")
+        },
+    )
+    .run()
+    .unwrap();
+
+    let session = alice_store.load().unwrap();
+    assert_eq!(
+        session.drivers.all(),
+        vec!["alex@one.com", "alex@two.com", "carol@three.com"]
+    );
+    assert!(session.drivers.is_away("carol@three.com"));
+    assert_eq!(session.drivers.name("carol@three.com"), "carol");
+}
+
+#[test]
+fn test_done_with_message() {
+    let repos = setup_repos(2);
+    let alice = repos.alice();
+    let bob = repos.bob();
+
+    let alice_git = git::GitCommand::new(Some(alice.path.clone()), "origin".to_string()).unwrap();
+    let alice_store = session::SessionStore::new(&alice_git);
+    let bob_git = git::GitCommand::new(Some(bob.path.clone()), "origin".to_string()).unwrap();
+    let bob_store = session::SessionStore::new(&bob_git);
+
+    let prompter = MockPrompter::new()
+        .with_string("mob-session")
+        .with_string("main")
+        .with_number(10)
+        .with_string("mob sync");
+    cmd::Start::new(
+        &alice_git,
+        &alice_store,
+        &prompter,
+        cmd::StartOpts { minutes: Some(0) },
+        create_test_config("alice"),
+    )
+    .run()
+    .unwrap();
+    alice.write_file("alice.txt", "Alice's work");
+    cmd::Next::new(
+        &alice_git,
+        &alice_store,
+        &MockPrompter::new(),
+        create_test_config("alice"),
+    )
+    .run()
+    .unwrap();
+
+    cmd::Start::new(
+        &bob_git,
+        &bob_store,
+        &MockPrompter::new(),
+        cmd::StartOpts { minutes: Some(0) },
+        create_test_config("bob"),
+    )
+    .run()
+    .unwrap();
+    bob.write_file("bob.txt", "Bob's work");
+    cmd::Done::new(
+        &bob_git,
+        &bob_store,
+        &MockPrompter::new(),
+        cmd::DoneOpts {
+            message: Some("Add work".to_string()),
+            ..Default::default()
+        },
+        create_test_config("bob"),
+    )
+    .run()
+    .unwrap();
+
+    assert_eq!(bob.git_ok(&["status", "--short"]), "");
+    assert_eq!(
+        bob.git_ok(&["log", "-1", "--format=%B"]),
+        "Add work\n\nCo-authored-by: alice <alice@test.local>"
+    );
+    assert_eq!(bob.git_ok(&["log", "-1", "--format=%an"]), "bob");
+}
+
+#[test]
+fn test_concurrent_changes() {
+    let repos = setup_repos(3);
+    let alice = repos.alice();
+    let bob = repos.bob();
+    let carol = repos.carol();
+
+    let alice_git = git::GitCommand::new(Some(alice.path.clone()), "origin".to_string()).unwrap();
+    let alice_store = session::SessionStore::new(&alice_git);
+    let bob_git = git::GitCommand::new(Some(bob.path.clone()), "origin".to_string()).unwrap();
+    let bob_store = session::SessionStore::new(&bob_git);
+    let carol_git = git::GitCommand::new(Some(carol.path.clone()), "origin".to_string()).unwrap();
+    let carol_store = session::SessionStore::new(&carol_git);
+
+    let prompter = MockPrompter::new()
+        .with_string("mob-session")
+        .with_string("main")
+        .with_number(10)
+        .with_string("mob sync");
+    cmd::Start::new(
+        &alice_git,
+        &alice_store,
+        &prompter,
+        cmd::StartOpts { minutes: Some(0) },
+        create_test_config("alice"),
+    )
+    .run()
+    .unwrap();
+    cmd::Next::new(
+        &alice_git,
+        &alice_store,
+        &MockPrompter::new(),
+        create_test_config("alice"),
+    )
+    .run()
+    .unwrap();
+
+    // Carol decides on a session that bob changes before she saves
+    let stale = carol_store.load().unwrap();
+    cmd::Join::new(
+        cmd::JoinOpts {
+            after: Some("alice".to_string()),
+        },
+        &bob_store,
+        &MockPrompter::new(),
+        create_test_config("bob"),
+    )
+    .run()
+    .unwrap();
+
+    // Her change is applied again on top of bob's
+    let session = carol_store
+        .transition(stale.clone(), &|session| {
+            let drivers = session
+                .drivers
+                .clone()
+                .insert(None, session::Driver::new("carol", None, None));
+            Ok((session::Session { drivers, ..session }, vec![]))
+        })
+        .unwrap();
+    assert_eq!(session.drivers.all(), vec!["alice", "bob", "carol"]);
+    assert_eq!(
+        alice_store.load().unwrap().drivers.all(),
+        vec!["alice", "bob", "carol"]
+    );
+
+    // A turn change decided on the old state no longer applies
+    cmd::Start::new(
+        &bob_git,
+        &bob_store,
+        &MockPrompter::new().with_selection(0),
+        cmd::StartOpts { minutes: Some(0) },
+        create_test_config("bob"),
+    )
+    .run()
+    .unwrap();
+    let result = carol_store.transition(stale.clone(), &|session| {
+        session::ensure_unchanged(&stale.state, &session)?;
+        Ok((session, vec![]))
+    });
+    let err = result.unwrap_err().to_string();
+    assert!(
+        err.starts_with("someone else changed the session"),
+        "{}",
+        err
+    );
+}
+
+#[test]
+fn test_file_store() {
+    let repos = setup_repos(2);
+    let alice = repos.alice();
+    let bob = repos.bob();
+    let meta_dir = tempfile::tempdir().unwrap();
+
+    let alice_git = git::GitCommand::new(Some(alice.path.clone()), "origin".to_string()).unwrap();
+    let alice_files = git::FileStore::new(meta_dir.path().to_path_buf());
+    let alice_store = session::SessionStore::new(&alice_files);
+    let bob_git = git::GitCommand::new(Some(bob.path.clone()), "origin".to_string()).unwrap();
+    let bob_files = git::FileStore::new(meta_dir.path().to_path_buf());
+    let bob_store = session::SessionStore::new(&bob_files);
+
+    let prompter = MockPrompter::new()
+        .with_string("mob-session")
+        .with_string("main")
+        .with_number(10)
+        .with_string("mob sync");
+    cmd::Start::new(
+        &alice_git,
+        &alice_store,
+        &prompter,
+        cmd::StartOpts { minutes: Some(0) },
+        create_test_config("alice"),
+    )
+    .run()
+    .unwrap();
+    alice.write_file("alice.txt", "Alice's work");
+    cmd::Next::new(
+        &alice_git,
+        &alice_store,
+        &MockPrompter::new(),
+        create_test_config("alice"),
+    )
+    .run()
+    .unwrap();
+
+    cmd::Start::new(
+        &bob_git,
+        &bob_store,
+        &MockPrompter::new(),
+        cmd::StartOpts { minutes: Some(0) },
+        create_test_config("bob"),
+    )
+    .run()
+    .unwrap();
+
+    let session = alice_store.load().unwrap();
+    assert!(matches!(
+        session.state,
+        session::State::Working { driver, .. } if driver == "bob"
+    ));
+    assert_eq!(alice_store.history().unwrap().events().len(), 3);
+    assert!(bob.file_exists("alice.txt"));
+
+    // Nothing about the session is pushed
+    assert!(meta_dir.path().join("default").join("data").exists());
+    let heads = alice.git_ok(&["ls-remote", "--heads", "origin"]);
+    assert!(!heads.contains("mob-meta"), "{}", heads);
+}
+
+#[test]
+fn test_dry_run() {
+    let repos = setup_repos(1);
+    let alice = repos.alice();
+
+    let alice_git = git::GitCommand::new(Some(alice.path.clone()), "origin".to_string()).unwrap();
+    let alice_store = session::SessionStore::new(&alice_git);
+    let read_only_git = git::GitCommand::new(Some(alice.path.clone()), "origin".to_string())
+        .unwrap()
+        .read_only();
+    let read_only_store = session::SessionStore::new(&read_only_git);
+    let dry_git = git::DryRun::new(&alice_git);
+    let dry_store = session::DryRun::new(&read_only_store);
+    let dry_config = || config::Config {
+        dry_run: true,
+        ..create_test_config("alice")
+    };
+
+    let prompter = || {
+        MockPrompter::new()
+            .with_string("mob-session")
+            .with_string("main")
+            .with_number(10)
+            .with_string("mob sync")
+    };
+    cmd::Start::new(
+        &dry_git,
+        &dry_store,
+        &prompter(),
+        cmd::StartOpts { minutes: Some(0) },
+        dry_config(),
+    )
+    .run()
+    .unwrap();
+
+    // Neither a branch nor a session was created
+    assert_eq!(alice.git_ok(&["branch", "--list", "mob-session"]), "");
+    assert!(matches!(
+        alice_store.load_or_default().unwrap().state,
+        session::State::Stopped
+    ));
+
+    cmd::Start::new(
+        &alice_git,
+        &alice_store,
+        &prompter(),
+        cmd::StartOpts { minutes: Some(0) },
+        create_test_config("alice"),
+    )
+    .run()
+    .unwrap();
+    alice.write_file("alice.txt", "Alice's work");
+    // The session is read from the remote, the local branch is left alone
+    alice.git_ok(&["branch", "--force", "mob-meta", "main"]);
+
+    cmd::Done::new(
+        &dry_git,
+        &dry_store,
+        &MockPrompter::new(),
+        cmd::DoneOpts::default(),
+        dry_config(),
+    )
+    .run()
+    .unwrap();
+
+    // The work and the turn are left as they were
+    assert_eq!(
+        alice.git_ok(&["rev-parse", "--abbrev-ref", "HEAD"]),
+        "mob-session"
+    );
+    assert!(alice.file_exists("alice.txt"));
+    assert!(matches!(
+        read_only_store.load().unwrap().state,
+        session::State::Working { .. }
+    ));
+    assert_eq!(
+        alice.git_ok(&["rev-parse", "mob-meta"]),
+        alice.git_ok(&["rev-parse", "main"])
+    );
+    assert!(matches!(
+        alice_store.load().unwrap().state,
+        session::State::Working { .. }
+    ));
+}
+
+#[test]
+fn test_sync_base() {
+    let repos = setup_repos(2);
+    let alice = repos.alice();
+    let bob = repos.bob();
+
+    let alice_git = git::GitCommand::new(Some(alice.path.clone()), "origin".to_string()).unwrap();
+    let alice_store = session::SessionStore::new(&alice_git);
+
+    let prompter = MockPrompter::new()
+        .with_string("mob-session")
+        .with_string("main")
+        .with_number(10)
+        .with_string("mob sync");
+    cmd::Start::new(
+        &alice_git,
+        &alice_store,
+        &prompter,
+        cmd::StartOpts { minutes: Some(0) },
+        create_test_config("alice"),
+    )
+    .run()
+    .unwrap();
+    alice.write_file("alice.txt", "Alice's work");
+
+    // Someone outside the mob changes main
+    let push_to_main = |name: &str| {
+        bob.write_file(name, "hotfix");
+        bob.git_ok(&["add", name]);
+        bob.git_ok(&["commit", "-m", name]);
+        bob.git_ok(&["push", "origin", "main"]);
+    };
+    push_to_main("hotfix.txt");
+
+    cmd::SyncBase::new(
+        cmd::SyncBaseOpts::default(),
+        &alice_git,
+        &alice_store,
+        create_test_config("alice"),
+    )
+    .run()
+    .unwrap();
+
+    assert!(alice.file_exists("hotfix.txt"));
+    assert!(alice.file_exists("alice.txt"));
+    assert_eq!(
+        alice.git_ok(&["rev-list", "--count", "origin/mob-session..origin/main"]),
+        "0"
+    );
+    let history = alice_store.history().unwrap();
+    assert_eq!(
+        history.events().last().unwrap().kind,
+        session::EventKind::SyncBase { commits: 1 }
+    );
+
+    push_to_main("second.txt");
+    cmd::SyncBase::new(
+        cmd::SyncBaseOpts { rebase: true },
+        &alice_git,
+        &alice_store,
+        create_test_config("alice"),
+    )
+    .run()
+    .unwrap();
+
+    assert!(alice.file_exists("second.txt"));
+    assert_eq!(
+        alice.git_ok(&["rev-list", "--count", "mob-session..origin/main"]),
+        "0"
+    );
+    assert_eq!(
+        alice.git_ok(&["rev-parse", "origin/mob-session"]),
+        alice.git_ok(&["rev-parse", "HEAD"])
+    );
+}
+
+#[test]
+fn test_sync_base_up_to_date_keeps_work() {
+    let repos = setup_repos(2);
+    let alice = repos.alice();
+    let bob = repos.bob();
+
+    let alice_git = git::GitCommand::new(Some(alice.path.clone()), "origin".to_string()).unwrap();
+    let alice_store = session::SessionStore::new(&alice_git);
+    let bob_git = git::GitCommand::new(Some(bob.path.clone()), "origin".to_string()).unwrap();
+    let bob_store = session::SessionStore::new(&bob_git);
+
+    let prompter = MockPrompter::new()
+        .with_string("mob-session")
+        .with_string("main")
+        .with_number(10)
+        .with_string("mob sync");
+    cmd::Start::new(
+        &alice_git,
+        &alice_store,
+        &prompter,
+        cmd::StartOpts { minutes: Some(0) },
+        create_test_config("alice"),
+    )
+    .run()
+    .unwrap();
+    alice.write_file("alice.txt", "Alice's work");
+
+    // Nothing to bring in, the work stays uncommitted for next
+    cmd::SyncBase::new(
+        cmd::SyncBaseOpts::default(),
+        &alice_git,
+        &alice_store,
+        create_test_config("alice"),
+    )
+    .run()
+    .unwrap();
+    assert_eq!(alice.git_ok(&["status", "--short"]), "?? alice.txt");
+
+    cmd::Next::new(
+        &alice_git,
+        &alice_store,
+        &MockPrompter::new(),
+        create_test_config("alice"),
+    )
+    .run()
+    .unwrap();
+    cmd::Start::new(
+        &bob_git,
+        &bob_store,
+        &MockPrompter::new(),
+        cmd::StartOpts { minutes: Some(0) },
+        create_test_config("bob"),
+    )
+    .run()
+    .unwrap();
+    assert_eq!(bob.read_file("alice.txt"), "Alice's work");
+}
+
+/// Alice takes a turn, then Bob starts and leaves his work uncommitted
+fn two_turns(repos: &TestRepos) {
+    let alice_git =
+        git::GitCommand::new(Some(repos.alice().path.clone()), "origin".to_string()).unwrap();
+    let alice_store = session::SessionStore::new(&alice_git);
+    let bob_git =
+        git::GitCommand::new(Some(repos.bob().path.clone()), "origin".to_string()).unwrap();
+    let bob_store = session::SessionStore::new(&bob_git);
+
+    let prompter = MockPrompter::new()
+        .with_string("mob-session")
+        .with_string("main")
+        .with_number(10)
+        .with_string("mob sync");
+    cmd::Start::new(
+        &alice_git,
+        &alice_store,
+        &prompter,
+        cmd::StartOpts { minutes: Some(0) },
+        create_test_config("alice"),
+    )
+    .run()
+    .unwrap();
+    repos.alice().write_file("alice.txt", "Alice's work");
+    cmd::Next::new(
+        &alice_git,
+        &alice_store,
+        &MockPrompter::new(),
+        create_test_config("alice"),
+    )
+    .run()
+    .unwrap();
+
+    cmd::Start::new(
+        &bob_git,
+        &bob_store,
+        &MockPrompter::new(),
+        cmd::StartOpts { minutes: Some(0) },
+        create_test_config("bob"),
+    )
+    .run()
+    .unwrap();
+    repos.bob().write_file("bob.txt", "Bob's work");
+}
+
+fn bob_done(repos: &TestRepos, opts: cmd::DoneOpts) {
+    let bob_git =
+        git::GitCommand::new(Some(repos.bob().path.clone()), "origin".to_string()).unwrap();
+    let bob_store = session::SessionStore::new(&bob_git);
+    cmd::Done::new(
+        &bob_git,
+        &bob_store,
+        &MockPrompter::new(),
+        opts,
+        create_test_config("bob"),
+    )
+    .run()
+    .unwrap();
+    assert!(matches!(
+        bob_store.load().unwrap().state,
+        session::State::Stopped
+    ));
+    assert_eq!(
+        repos
+            .bob()
+            .git_ok(&["ls-remote", "--heads", "origin", "mob-session"]),
+        ""
+    );
+}
+
+#[test]
+fn test_done_credits_co_authors() {
+    let repos = setup_repos(2);
+    let bob = repos.bob();
+    two_turns(&repos);
+
+    bob_done(&repos, cmd::DoneOpts::default());
+
+    // Alice is credited in the prepared commit message
+    let squash_msg = bob.read_file(".git/SQUASH_MSG");
+    assert!(squash_msg.ends_with("Co-authored-by: alice <alice@test.local>\n"));
+}
+
+#[test]
+fn test_done_squash_commit() {
+    let repos = setup_repos(2);
+    let bob = repos.bob();
+    two_turns(&repos);
+
+    bob_done(
+        &repos,
+        cmd::DoneOpts {
+            strategy: Some(session::DoneStrategy::SquashCommit),
+            ..Default::default()
+        },
+    );
+
+    assert_eq!(bob.git_ok(&["rev-parse", "--abbrev-ref", "HEAD"]), "main");
+    assert_eq!(bob.git_ok(&["status", "--short"]), "");
+    assert_eq!(
+        bob.git_ok(&["rev-list", "--count", "origin/main..HEAD"]),
+        "1"
+    );
+    let message = bob.git_ok(&["log", "-1", "--format=%B"]);
+    assert!(message.starts_with("Squashed commit of the following:"));
+    assert!(message.ends_with("Co-authored-by: alice <alice@test.local>"));
+    assert!(bob.file_exists("alice.txt"));
+    assert!(bob.file_exists("bob.txt"));
+}
+
+#[test]
+fn test_done_merge_from_settings() {
+    let repos = setup_repos(2);
+    let bob = repos.bob();
+    two_turns(&repos);
+
+    let bob_git = git::GitCommand::new(Some(bob.path.clone()), "origin".to_string()).unwrap();
+    let bob_store = session::SessionStore::new(&bob_git);
+    cmd::Settings::new(
+        cmd::SettingsOpts {
+            done_strategy: Some(session::DoneStrategy::Merge),
+            ..Default::default()
+        },
+        &bob_store,
+        &MockPrompter::new(),
+    )
+    .run()
+    .unwrap();
+
+    bob_done(&repos, cmd::DoneOpts::default());
+
+    assert_eq!(bob.git_ok(&["status", "--short"]), "");
+    let parents = bob.git_ok(&["log", "-1", "--format=%P"]);
+    assert_eq!(parents.split(' ').count(), 2);
+    assert_eq!(
+        bob.git_ok(&["log", "-1", "--format=%B"]),
+        "Merge mob branch 'mob-session'\n\nCo-authored-by: alice <alice@test.local>"
+    );
+    // Every turn is kept
+    assert_eq!(
+        bob.git_ok(&["log", "--format=%an", "--no-merges", "origin/main..HEAD"]),
+        "bob\nalice"
+    );
+}
+
+#[test]
+fn test_done_rebase() {
+    let repos = setup_repos(3);
+    let bob = repos.bob();
+    let carol = repos.carol();
+    two_turns(&repos);
+
+    // main moves on while the mob works
+    carol.write_file("carol.txt", "Carol's work");
+    carol.git_ok(&["add", "carol.txt"]);
+    carol.git_ok(&["commit", "-m", "carol"]);
+    carol.git_ok(&["push", "origin", "main"]);
+
+    bob_done(
+        &repos,
+        cmd::DoneOpts {
+            strategy: Some(session::DoneStrategy::Rebase),
+            ..Default::default()
+        },
+    );
+
+    assert_eq!(bob.git_ok(&["rev-parse", "--abbrev-ref", "HEAD"]), "main");
+    assert_eq!(bob.git_ok(&["status", "--short"]), "");
+    assert_eq!(
+        bob.git_ok(&["log", "--format=%an", "origin/main..HEAD"]),
+        "bob\nalice"
+    );
+    assert_eq!(bob.git_ok(&["rev-list", "--merges", "HEAD"]), "");
+    assert!(bob.file_exists("carol.txt"));
+}
+
+#[test]
+fn test_done_rebase_autosquash() {
+    let repos = setup_repos(2);
+    let bob = repos.bob();
+    two_turns(&repos);
+
+    // Bob's work fixes up Alice's turn
+    bob.git_ok(&["add", "bob.txt"]);
+    bob.git_ok(&["commit", "-m", "fixup! mob sync"]);
+
+    bob_done(
+        &repos,
+        cmd::DoneOpts {
+            strategy: Some(session::DoneStrategy::RebaseAutosquash),
+            ..Default::default()
+        },
+    );
+
+    assert_eq!(bob.git_ok(&["status", "--short"]), "");
+    assert_eq!(
+        bob.git_ok(&["log", "--format=%an %s", "origin/main..HEAD"]),
+        "alice mob sync"
+    );
+    assert!(bob.file_exists("bob.txt"));
+}
+
+#[test]
+fn test_done_review() {
+    let repos = setup_repos(2);
+    let bob = repos.bob();
+    two_turns(&repos);
+
+    bob_done(
+        &repos,
+        cmd::DoneOpts {
+            message: Some("Add work".to_string()),
+            strategy: Some(session::DoneStrategy::Review),
+        },
+    );
+
+    assert_eq!(
+        bob.git_ok(&["rev-parse", "--abbrev-ref", "HEAD"]),
+        "mob-session-review"
+    );
+    assert_eq!(
+        bob.git_ok(&["rev-parse", "origin/mob-session-review"]),
+        bob.git_ok(&["rev-parse", "HEAD"])
+    );
+    assert_eq!(
+        bob.git_ok(&["log", "-1", "--format=%B"]),
+        "Add work\n\nCo-authored-by: alice <alice@test.local>"
+    );
+    // The base branch is left alone
+    assert_eq!(
+        bob.git_ok(&["rev-parse", "main"]),
+        bob.git_ok(&["rev-parse", "origin/main"])
+    );
+}
+
+#[test]
+fn test_undo_handover() {
+    let repos = setup_repos(2);
+    let alice = repos.alice();
+    let bob = repos.bob();
+
+    let alice_git = git::GitCommand::new(Some(alice.path.clone()), "origin".to_string()).unwrap();
+    let alice_store = session::SessionStore::new(&alice_git);
+    let bob_git = git::GitCommand::new(Some(bob.path.clone()), "origin".to_string()).unwrap();
+    let bob_store = session::SessionStore::new(&bob_git);
+
+    let prompter = MockPrompter::new()
+        .with_string("mob-session")
+        .with_string("main")
+        .with_number(10)
+        .with_string("mob sync");
+    cmd::Start::new(
+        &alice_git,
+        &alice_store,
+        &prompter,
+        cmd::StartOpts { minutes: Some(0) },
+        create_test_config("alice"),
+    )
+    .run()
+    .unwrap();
+    cmd::Join::new(
+        cmd::JoinOpts {
+            after: Some("alice".to_string()),
+        },
+        &bob_store,
+        &MockPrompter::new(),
+        create_test_config("bob"),
+    )
+    .run()
+    .unwrap();
+
+    let before = alice.git_ok(&["rev-parse", "HEAD"]);
+    alice.write_file("alice.txt", "Alice's work");
+    cmd::Next::new(
+        &alice_git,
+        &alice_store,
+        &MockPrompter::new(),
+        create_test_config("alice"),
+    )
+    .run()
+    .unwrap();
+
+    // Only the driver who handed over can undo
+    cmd::Undo::new(&bob_git, &bob_store, create_test_config("bob"))
+        .run()
+        .unwrap();
+    assert!(matches!(
+        bob_store.load().unwrap().state,
+        session::State::WaitingForNext { .. }
+    ));
+
+    cmd::Undo::new(&alice_git, &alice_store, create_test_config("alice"))
+        .run()
+        .unwrap();
+
+    // The turn goes on as started, with a fresh lease
+    let session = alice_store.load().unwrap();
+    assert!(matches!(
+        session.state,
+        session::State::Working {
+            ref driver,
+            work_duration: 0,
+            last_seen: Some(_),
+            ..
+        } if driver == "alice"
+    ));
+    assert!(!session.state.lease_expired(chrono::Utc::now()));
+    assert_eq!(session.navigator, Some("bob".to_string()));
+    assert_eq!(alice.git_ok(&["rev-parse", "HEAD"]), before);
+    assert_eq!(alice.git_ok(&["status", "--short"]), "?? alice.txt");
+    assert_eq!(alice.read_file("alice.txt"), "Alice's work");
+    alice.git_ok(&["fetch", "origin"]);
+    assert_eq!(alice.git_ok(&["rev-parse", "origin/mob-session"]), before);
+
+    let history = alice_store.history().unwrap();
+    assert_eq!(
+        history.events().last().unwrap().kind,
+        session::EventKind::Undo
+    );
+    assert_eq!(history.turns().len(), 1);
+
+    // Once the next driver has started it's too late
+    cmd::Next::new(
+        &alice_git,
+        &alice_store,
+        &MockPrompter::new(),
+        create_test_config("alice"),
+    )
+    .run()
+    .unwrap();
+    cmd::Start::new(
+        &bob_git,
+        &bob_store,
+        &MockPrompter::new(),
+        cmd::StartOpts { minutes: Some(0) },
+        create_test_config("bob"),
+    )
+    .run()
+    .unwrap();
+    cmd::Undo::new(&alice_git, &alice_store, create_test_config("alice"))
+        .run()
+        .unwrap();
+    assert!(matches!(
+        alice_store.load().unwrap().state,
+        session::State::Working { ref driver, .. } if driver == "bob"
+    ));
+    assert!(bob.file_exists("alice.txt"));
+}
+
+#[test]
+fn test_undo_keeps_own_commits() {
+    let repos = setup_repos(1);
+    let alice = repos.alice();
+
+    let alice_git = git::GitCommand::new(Some(alice.path.clone()), "origin".to_string()).unwrap();
+    let alice_store = session::SessionStore::new(&alice_git);
+
+    let prompter = MockPrompter::new()
+        .with_string("mob-session")
+        .with_string("main")
+        .with_number(10)
+        .with_string("mob sync");
+    cmd::Start::new(
+        &alice_git,
+        &alice_store,
+        &prompter,
+        cmd::StartOpts { minutes: Some(0) },
+        create_test_config("alice"),
+    )
+    .run()
+    .unwrap();
+
+    // Committed and pushed by hand, next has nothing to commit
+    alice.write_file("alice.txt", "Alice's work");
+    alice.git_ok(&["add", "alice.txt"]);
+    alice.git_ok(&["commit", "-m", "Alice's work"]);
+    alice.git_ok(&["push", "origin", "mob-session"]);
+    let committed = alice.git_ok(&["rev-parse", "HEAD"]);
+    cmd::Next::new(
+        &alice_git,
+        &alice_store,
+        &MockPrompter::new(),
+        create_test_config("alice"),
+    )
+    .run()
+    .unwrap();
+
+    cmd::Undo::new(&alice_git, &alice_store, create_test_config("alice"))
+        .run()
+        .unwrap();
+
+    assert!(matches!(
+        alice_store.load().unwrap().state,
+        session::State::Working { ref driver, .. } if driver == "alice"
+    ));
+    assert_eq!(alice.git_ok(&["rev-parse", "HEAD"]), committed);
+    assert_eq!(alice.git_ok(&["status", "--short"]), "");
+    alice.git_ok(&["fetch", "origin"]);
+    assert_eq!(
+        alice.git_ok(&["rev-parse", "origin/mob-session"]),
+        committed
+    );
+}
+
+#[test]
+fn test_backups_are_never_overwritten() {
+    let repos = setup_repos(1);
+    let alice = repos.alice();
+
+    let git = git::GitCommand::new(Some(alice.path.clone()), "origin".to_string()).unwrap();
+    let first = git.backup("main").unwrap().unwrap();
+    alice.write_file("alice.txt", "Alice's work");
+    alice.git_ok(&["add", "alice.txt"]);
+    alice.git_ok(&["commit", "-m", "Alice's work"]);
+    let second = git.backup("main").unwrap().unwrap();
+
+    assert_ne!(first, second);
+    assert_eq!(
+        alice.git_ok(&["rev-parse", &first]),
+        alice.git_ok(&["rev-parse", "main~1"])
+    );
+    assert_eq!(git.saved_work().unwrap().len(), 2);
+}
+
+#[test]
+fn test_backup_unpushed_commits() {
+    let repos = setup_repos(1);
+    let alice = repos.alice();
+
+    let alice_git = git::GitCommand::new(Some(alice.path.clone()), "origin".to_string()).unwrap();
+    let alice_store = session::SessionStore::new(&alice_git);
+
+    let prompter = MockPrompter::new()
+        .with_string("mob-session")
+        .with_string("main")
+        .with_number(10)
+        .with_string("mob sync");
+    cmd::Start::new(
+        &alice_git,
+        &alice_store,
+        &prompter,
+        cmd::StartOpts { minutes: Some(0) },
+        create_test_config("alice"),
+    )
+    .run()
+    .unwrap();
+    cmd::Next::new(
+        &alice_git,
+        &alice_store,
+        &MockPrompter::new(),
+        create_test_config("alice"),
+    )
+    .run()
+    .unwrap();
+
+    // A commit that never made it to the remote
+    alice.write_file("local.txt", "not pushed");
+    alice.git_ok(&["add", "local.txt"]);
+    alice.git_ok(&["commit", "-m", "local"]);
+    let local = alice.git_ok(&["rev-parse", "HEAD"]);
+
+    cmd::Start::new(
+        &alice_git,
+        &alice_store,
+        &MockPrompter::new(),
+        cmd::StartOpts { minutes: Some(0) },
+        create_test_config("alice"),
+    )
+    .run()
+    .unwrap();
+
+    assert!(!alice.file_exists("local.txt"));
+    let backup = alice.git_ok(&["for-each-ref", "--format=%(objectname)", "refs/mob/backup"]);
+    assert_eq!(backup, local);
+}
+
+#[test]
+fn test_rescue_stash_on_new_branch() {
+    let repos = setup_repos(1);
+    let alice = repos.alice();
+    alice.write_file("dirty.txt", "uncommitted");
+
+    let git = git::GitCommand::new(Some(alice.path.clone()), "origin".to_string()).unwrap();
+    let store = session::SessionStore::new(&git);
+    let prompter = MockPrompter::new()
+        .with_selection(1) // Stash changes
+        .with_string("mob-session")
+        .with_string("main")
+        .with_number(10)
+        .with_string("mob sync");
+    cmd::Start::new(
+        &git,
+        &store,
+        &prompter,
+        cmd::StartOpts { minutes: Some(0) },
+        create_test_config("alice"),
+    )
+    .run()
+    .unwrap();
+    assert!(!alice.file_exists("dirty.txt"));
+
+    let saved = git.saved_work().unwrap();
+    assert_eq!(saved.len(), 1);
+    assert_eq!(saved[0].kind, git::SavedKind::Stash);
+    assert_eq!(saved[0].branch, Some("main".to_string()));
+
+    // Answers are popped in reverse
+    let prompter = MockPrompter::new()
+        .with_string("rescued")
+        .with_selection(1) // Onto a new branch
+        .with_selection(0); // The stash
+    cmd::Rescue::new(&git, &prompter).run().unwrap();
+
+    assert_eq!(alice.git_ok(&["branch", "--show-current"]), "rescued");
+    assert_eq!(alice.read_file("dirty.txt"), "uncommitted");
+    assert_eq!(git.saved_work().unwrap().len(), 1);
+}
+
+#[test]
+fn test_rescue_discarded_on_current_branch() {
+    let repos = setup_repos(1);
+    let alice = repos.alice();
+    alice.write_file("dirty.txt", "uncommitted");
+
+    let git = git::GitCommand::new(Some(alice.path.clone()), "origin".to_string()).unwrap();
+    let store = session::SessionStore::new(&git);
+    let prompter = MockPrompter::new()
+        .with_selection(2) // Discard changes
+        .with_string("mob-session")
+        .with_string("main")
+        .with_number(10)
+        .with_string("mob sync");
+    cmd::Start::new(
+        &git,
+        &store,
+        &prompter,
+        cmd::StartOpts { minutes: Some(0) },
+        create_test_config("alice"),
+    )
+    .run()
+    .unwrap();
+
+    let saved = git.saved_work().unwrap();
+    assert_eq!(saved.len(), 1);
+    assert_eq!(saved[0].kind, git::SavedKind::Worktree);
+    assert_eq!(saved[0].branch, Some("main".to_string()));
+    assert_eq!(
+        git.diffstat(&saved[0].range()).unwrap(),
+        "1 file changed, 1 insertion(+)"
+    );
+
+    let prompter = MockPrompter::new()
+        .with_selection(0) // Onto the current branch
+        .with_selection(0); // The backup
+    cmd::Rescue::new(&git, &prompter).run().unwrap();
+
+    assert_eq!(alice.git_ok(&["branch", "--show-current"]), "mob-session");
+    assert_eq!(alice.read_file("dirty.txt"), "uncommitted");
+}
+
+#[test]
+fn test_bundle_handover() {
+    let repos = setup_repos(2);
+    let alice = repos.alice();
+    let bob = repos.bob();
+    let bundles = tempfile::TempDir::new().unwrap();
+
+    // Nobody can reach a remote
+    alice.git_ok(&["remote", "remove", "origin"]);
+    bob.git_ok(&["remote", "remove", "origin"]);
+
+    let bundle = |path: &std::path::Path| {
+        let git = git::GitCommand::new(Some(path.to_path_buf()), "origin".to_string()).unwrap();
+        git::Bundle::new(git, bundles.path().to_path_buf())
+    };
+    let config = |name: &str| config::Config {
+        bundle_dir: Some(bundles.path().to_path_buf()),
+        ..create_test_config(name)
+    };
+    let alice_git = bundle(&alice.path);
+    let alice_store = session::SessionStore::new(&alice_git);
+    let bob_git = bundle(&bob.path);
+    let bob_store = session::SessionStore::new(&bob_git);
+
+    let prompter = MockPrompter::new()
+        .with_string("mob-session")
+        .with_string("main")
+        .with_number(10)
+        .with_string("mob sync");
+    cmd::Start::new(
+        &alice_git,
+        &alice_store,
+        &prompter,
+        cmd::StartOpts { minutes: Some(0) },
+        config("alice"),
+    )
+    .run()
+    .unwrap();
+    alice.write_file("alice.txt", "Alice's work");
+    cmd::Next::new(
+        &alice_git,
+        &alice_store,
+        &MockPrompter::new(),
+        config("alice"),
+    )
+    .run()
+    .unwrap();
+
+    let newest = |dir: &std::path::Path| {
+        let mut names: Vec<_> = std::fs::read_dir(dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .filter(|path| path.extension().is_some_and(|ext| ext == "bundle"))
+            .collect();
+        // Bundles are numbered, 10 comes after 9
+        names.sort_by_key(|path| {
+            path.file_stem()
+                .and_then(|stem| stem.to_str()?.parse::<u64>().ok())
+        });
+        names.pop().unwrap()
+    };
+    let heads = alice.git_ok(&[
+        "bundle",
+        "list-heads",
+        newest(bundles.path()).to_str().unwrap(),
+    ]);
+    assert!(heads.contains("refs/mob/shared/mob-session"));
+    assert!(heads.contains("refs/mob/shared/mob-meta"));
+    assert!(heads.contains("refs/mob/shared/main"));
+
+    cmd::Start::new(
+        &bob_git,
+        &bob_store,
+        &MockPrompter::new(),
+        cmd::StartOpts { minutes: Some(0) },
+        config("bob"),
+    )
+    .run()
+    .unwrap();
+    assert_eq!(bob.git_ok(&["branch", "--show-current"]), "mob-session");
+    assert_eq!(bob.read_file("alice.txt"), "Alice's work");
+
+    // A dry run reads the newest bundle without importing it
+    let refs = alice.git_ok(&["for-each-ref"]);
+    let read_only = git::Bundle::new(
+        git::GitCommand::new(Some(alice.path.clone()), "origin".to_string())
+            .unwrap()
+            .read_only(),
+        bundles.path().to_path_buf(),
+    );
+    let read_only_store = session::SessionStore::new(&read_only);
+    assert!(matches!(
+        read_only_store.load().unwrap().state,
+        session::State::Working { ref driver, .. } if driver == "bob"
+    ));
+    assert_eq!(read_only_store.history().unwrap().turns().len(), 2);
+    assert_eq!(read_only_store.load_all().unwrap().len(), 1);
+    assert_eq!(alice.git_ok(&["for-each-ref"]), refs);
+
+    assert!(matches!(
+        alice_store.load().unwrap().state,
+        session::State::Working { ref driver, .. } if driver == "bob"
+    ));
+
+    bob.write_file("bob.txt", "Bob's work");
+    bob.git_ok(&["add", "bob.txt"]);
+    bob.git_ok(&["commit", "-m", "bob"]);
+    bob_git
+        .run(&["push", "--no-verify", "origin", "mob-session"])
+        .unwrap();
+
+    // Pushing on top of an old bundle is rejected like a push to a remote
+    alice.write_file("late.txt", "Too late");
+    alice.git_ok(&["add", "late.txt"]);
+    alice.git_ok(&["commit", "-m", "late"]);
+    assert!(
+        alice_git
+            .run(&["push", "--no-verify", "origin", "mob-session"])
+            .is_err()
+    );
+
+    cmd::Done::new(
+        &bob_git,
+        &bob_store,
+        &MockPrompter::new(),
+        cmd::DoneOpts::default(),
+        config("bob"),
+    )
+    .run()
+    .unwrap();
+    assert_eq!(bob.git_ok(&["branch", "--show-current"]), "main");
+    assert!(bob.file_exists("alice.txt"));
+    assert!(bob.file_exists("bob.txt"));
+    assert!(!bob.file_exists("late.txt"));
+
+    let heads = bob.git_ok(&[
+        "bundle",
+        "list-heads",
+        newest(bundles.path()).to_str().unwrap(),
+    ]);
+    assert!(!heads.contains("refs/mob/shared/mob-session"));
+    assert!(matches!(
+        alice_store.load().unwrap().state,
+        session::State::Stopped
+    ));
 }